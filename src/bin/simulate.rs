@@ -0,0 +1,6 @@
+use bracketlib_rougelike::combat_sim;
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    combat_sim::run(&args[1..]);
+}
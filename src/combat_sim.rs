@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use bracket_lib::random::RandomNumberGenerator;
+use specs::prelude::*;
+use specs::saveload::SimpleMarkerAllocator;
+
+use crate::components::{Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, InBackpack, MeleeWeapon, Name, NaturalAttackDefense, Overloaded, Pools, RangedWeapon, SerializeMe, Shield, Skills, Wearable};
+use crate::encumbrance_system::EncumbranceSystem;
+use crate::gamelog::GameLog;
+use crate::energy_system::{EnergySystem, ACTION_THRESHOLD, MELEE_COST};
+use crate::melee_combat_system::{armor_class, attack_hits, damage_roll, hit_roll, roll_natural_attack, shield_block, unarmed_weapon};
+use crate::raws::rawmaster::{spawn_named_entity, spawn_named_mob, SpawnType};
+use crate::raws::RAWS;
+use crate::spawner::player;
+
+const DEFAULT_FIGHTS: i32 = 1000;
+const MAX_ROUNDS: i32 = 1000;
+
+struct Combatant {
+    name: String,
+    attributes: Attributes,
+    skills: Skills,
+    max_hp: i32,
+    weapon: Option<MeleeWeapon>,
+    natural: Option<NaturalAttackDefense>,
+    shield: Option<Shield>,
+    armor_item_bonus: f32,
+    well_fed: bool,
+    overloaded: bool,
+    energy_gain: i32,
+}
+
+#[derive(Default)]
+struct CombatantStats {
+    wins: i32,
+    turns_to_kill: Vec<i32>,
+    attacks: i32,
+    misses: i32,
+    damage: BTreeMap<i32, i32>,
+}
+
+/// Runs `simulate <combatant> <combatant> [fights]`, where a combatant is either a mob id from the
+/// raws, `player` for the default starting kit, or `player:Item,Item` for a custom loadout
+pub fn run(args: &[String]) {
+    if args.len() < 2 {
+        println!("Usage: simulate <combatant> <combatant> [fights]");
+        println!("  combatant: a mob id, `player`, or `player:Item,Item,...`");
+        return;
+    }
+    let fights = args.get(2).and_then(|f| f.parse::<i32>().ok()).unwrap_or(DEFAULT_FIGHTS);
+
+    crate::raws::load_raws();
+    let mut ecs = World::new();
+    crate::register_components(&mut ecs);
+    ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+    ecs.insert(RandomNumberGenerator::new());
+    ecs.insert(GameLog::new());
+    // Systems reused here expect a player entity resource, even though neither combatant needs it
+    let placeholder = ecs.create_entity().build();
+    ecs.insert(placeholder);
+
+    let combatants = match (build_combatant(&mut ecs, &args[0]), build_combatant(&mut ecs, &args[1])) {
+        (Some(a), Some(b)) => [a, b],
+        _ => return,
+    };
+
+    let mut rng = RandomNumberGenerator::new();
+    let mut stats = [CombatantStats::default(), CombatantStats::default()];
+    let mut draws = 0;
+    for _ in 0..fights {
+        match fight(&mut rng, &combatants, &mut stats) {
+            Some((winner, rounds)) => {
+                stats[winner].wins += 1;
+                stats[winner].turns_to_kill.push(rounds);
+            }
+            None => draws += 1,
+        }
+    }
+
+    println!("{} fights: {} vs {}", fights, combatants[0].name, combatants[1].name);
+    if draws > 0 {
+        println!("{} fights hit the {} round limit", draws, MAX_ROUNDS);
+    }
+    for (combatant, stat) in combatants.iter().zip(stats.iter()) {
+        report(combatant, stat, fights);
+    }
+}
+
+fn build_combatant(ecs: &mut World, spec: &str) -> Option<Combatant> {
+    let entity = if spec == "player" {
+        player(ecs, 0, 0)
+    } else if let Some(loadout) = spec.strip_prefix("player:") {
+        let player_entity = player(ecs, 0, 0);
+        let starting_kit: Vec<Entity> = {
+            let entities = ecs.entities();
+            let equipped = ecs.read_storage::<Equipped>();
+            let backpack = ecs.read_storage::<InBackpack>();
+            entities
+                .join()
+                .filter(|e| {
+                    equipped.get(*e).is_some_and(|eq| eq.owner == player_entity)
+                        || backpack.get(*e).is_some_and(|bp| bp.owner == player_entity)
+                })
+                .collect()
+        };
+        ecs.delete_entities(&starting_kit).expect("Unable to remove starting kit");
+        for item in loadout.split(',').filter(|i| !i.is_empty()) {
+            if spawn_named_entity(&RAWS.lock().unwrap(), &item.to_string(), SpawnType::Equipped { by: player_entity }, ecs).is_none() {
+                println!("Unknown item: {}", item);
+                return None;
+            }
+        }
+        player_entity
+    } else {
+        match spawn_named_mob(&RAWS.lock().unwrap(), &spec.to_string(), SpawnType::AtPosition { x: 0, y: 0 }, ecs) {
+            Some(mob) => mob,
+            None => {
+                println!("Unknown mob: {}", spec);
+                return None;
+            }
+        }
+    };
+    ecs.maintain();
+    EncumbranceSystem {}.run_now(ecs);
+    ecs.maintain();
+
+    let equipped = ecs.read_storage::<Equipped>();
+    let mut weapon = None;
+    for (wielded, melee, _) in (&equipped, &ecs.read_storage::<MeleeWeapon>(), !&ecs.read_storage::<RangedWeapon>()).join() {
+        if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
+            weapon = Some(melee.clone());
+        }
+    }
+    let mut shield = None;
+    for (wielded, s) in (&equipped, &ecs.read_storage::<Shield>()).join() {
+        if wielded.owner == entity && wielded.slot == EquipmentSlot::Shield {
+            shield = Some(s.clone());
+        }
+    }
+    let mut armor_item_bonus = 0.0;
+    for (wielded, armor) in (&equipped, &ecs.read_storage::<Wearable>()).join() {
+        if wielded.owner == entity {
+            armor_item_bonus += armor.armor_class;
+        }
+    }
+    let attributes = ecs.read_storage::<Attributes>().get(entity).unwrap().clone();
+    let hunger = ecs.read_storage::<HungerClock>();
+    let overloaded = ecs.read_storage::<Overloaded>().get(entity).is_some();
+    let name = ecs.read_storage::<Name>().get(entity).map_or(spec.to_string(), |n| format!("{} ({})", n.name, spec));
+    Some(Combatant {
+        name,
+        energy_gain: EnergySystem::energy_gain(Some(&attributes), hunger.get(entity), overloaded),
+        well_fed: hunger.get(entity).is_some_and(|hc| hc.state == HungerState::WellFed),
+        overloaded,
+        attributes,
+        skills: ecs.read_storage::<Skills>().get(entity).unwrap().clone(),
+        max_hp: ecs.read_storage::<Pools>().get(entity).unwrap().hit_points.max,
+        weapon,
+        natural: ecs.read_storage::<NaturalAttackDefense>().get(entity).cloned(),
+        shield,
+        armor_item_bonus,
+    })
+}
+
+/// Returns the index of the winner and the number of attacks it needed, or None on a stalemate
+fn fight(rng: &mut RandomNumberGenerator, combatants: &[Combatant; 2], stats: &mut [CombatantStats; 2]) -> Option<(usize, i32)> {
+    let mut hp = [combatants[0].max_hp, combatants[1].max_hp];
+    let mut energy = [rng.range(0, ACTION_THRESHOLD), rng.range(0, ACTION_THRESHOLD)];
+    let mut attacks = [0, 0];
+
+    while attacks[0] + attacks[1] < MAX_ROUNDS * 2 {
+        for attacker in 0..2 {
+            let defender = 1 - attacker;
+            energy[attacker] = i32::min(energy[attacker] + combatants[attacker].energy_gain, ACTION_THRESHOLD);
+            if energy[attacker] < ACTION_THRESHOLD {
+                continue;
+            }
+            energy[attacker] -= MELEE_COST;
+            attacks[attacker] += 1;
+            let damage = attack(rng, &combatants[attacker], &combatants[defender]);
+            let attacker_stats = &mut stats[attacker];
+            attacker_stats.attacks += 1;
+            match damage {
+                None => attacker_stats.misses += 1,
+                Some(damage) => {
+                    *attacker_stats.damage.entry(damage).or_insert(0) += 1;
+                    hp[defender] -= damage;
+                }
+            }
+            if hp[defender] < 1 {
+                return Some((attacker, attacks[attacker]));
+            }
+        }
+    }
+    None
+}
+
+fn attack(rng: &mut RandomNumberGenerator, attacker: &Combatant, defender: &Combatant) -> Option<i32> {
+    let mut weapon = unarmed_weapon();
+    if let Some(nat) = &attacker.natural {
+        if let Some(natural_weapon) = roll_natural_attack(rng, nat) {
+            weapon = natural_weapon;
+        }
+    }
+    if let Some(wielded) = &attacker.weapon {
+        weapon = wielded.clone();
+    }
+    let (natural_roll, modified_hit_roll) = hit_roll(rng, &attacker.attributes, &attacker.skills, &weapon, attacker.well_fed, attacker.overloaded);
+    let armor_class = armor_class(defender.natural.as_ref(), &defender.attributes, &defender.skills, defender.armor_item_bonus, defender.overloaded);
+    if attack_hits(natural_roll, modified_hit_roll, armor_class) {
+        let mut damage = damage_roll(rng, &attacker.attributes, &attacker.skills, &weapon);
+        if let Some(shield) = &defender.shield {
+            if let Some(reduction) = shield_block(rng, shield, &defender.skills) {
+                damage -= i32::min(damage, reduction);
+            }
+        }
+        Some(damage)
+    } else {
+        None
+    }
+}
+
+fn report(combatant: &Combatant, stats: &CombatantStats, fights: i32) {
+    println!();
+    println!("{}", combatant.name);
+    println!("  win rate:      {:.1}%", stats.wins as f32 * 100.0 / fights as f32);
+    if !stats.turns_to_kill.is_empty() {
+        let total: i32 = stats.turns_to_kill.iter().sum();
+        println!("  turns to kill: {:.2}", total as f32 / stats.turns_to_kill.len() as f32);
+    }
+    if stats.attacks == 0 {
+        return;
+    }
+    println!("  hit rate:      {:.1}%", (stats.attacks - stats.misses) as f32 * 100.0 / stats.attacks as f32);
+    println!("  damage per hit:");
+    let most = stats.damage.values().copied().max().unwrap_or(1);
+    for (damage, count) in stats.damage.iter() {
+        let bar = "#".repeat(((count * 40) / most) as usize);
+        println!("    {:>3} {:>6} {}", damage, count, bar);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use bracket_lib::color::RGB;
 use bracket_lib::prelude::{FontCharType, Point};
 use serde::{Deserialize, Serialize};
@@ -9,6 +9,9 @@ use specs::saveload::ConvertSaveload;
 use specs::saveload::Marker;
 use specs_derive::*;
 
+use crate::gameclock::GameClock;
+use crate::spawner::PackIds;
+use crate::statistics_system::Statistics;
 use crate::map::Map;
 
 pub struct SerializeMe;
@@ -33,8 +36,55 @@ pub struct Monster {}
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Bystander {}
 
+/// A trader's stock is a count for each raw item id, topped back up every so often
 #[derive(Component, Serialize, Deserialize, Clone)]
-pub struct Vendor {}
+pub struct Vendor {
+    pub categories: Vec<String>,
+    pub stock: Vec<(String, i32)>,
+    pub restock_timer: i32,
+    pub hours: (i32, i32)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum SchedulePlace {
+    Home,
+    Landmark(String),
+    Anywhere,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduleEntry {
+    pub hour: i32,
+    pub place: SchedulePlace
+}
+
+/// Where someone wants to be through the day; each entry holds from its hour until the next one starts
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Schedule {
+    pub entries: Vec<ScheduleEntry>
+}
+
+impl Schedule {
+    pub fn place_at(&self, hour: i32) -> Option<&SchedulePlace> {
+        self.entries
+            .iter()
+            .rfind(|entry| entry.hour <= hour)
+            .or(self.entries.last())
+            .map(|entry| &entry.place)
+    }
+}
+
+/// Names the raw dialogue tree used when the player talks to this entity
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Dialogue {
+    pub tree: String
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Quests {
+    pub active: HashSet<String>,
+    pub completed: HashSet<String>
+}
 
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Name {
@@ -60,24 +110,38 @@ pub struct WantsToMelee {
     pub target: Entity
 }
 
-#[derive(Component, Debug, ConvertSaveload, Clone)]
+/// One blow waiting to be dealt. Whoever dealt it isn't saved, since they may be gone by the time
+/// the game is loaded.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Hit {
+    pub amount: i32,
+    pub from_player: bool,
+    #[serde(skip)]
+    pub source: Option<Entity>
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>
+    pub amount: Vec<Hit>
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32, from_player: bool, source: Option<Entity>) {
+        let hit = Hit { amount, from_player, source };
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push(hit);
         } else {
-            let dmg = SufferDamage { amount: vec![amount] };
+            let dmg = SufferDamage { amount: vec![hit] };
             store.insert(victim, dmg).expect("Unable to insert damage");
         }
     }
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct Item {}
+pub struct Item {
+    pub weight: f32,
+    pub value: f32
+}
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct ProvidesHealing {
@@ -110,7 +174,9 @@ pub struct WantsToDropItem {
 pub struct Consumable {}
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct Examinable {}
+pub struct Examinable {
+    pub description: String
+}
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Artefact {
@@ -140,7 +206,10 @@ pub struct Confusion {
 
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct SerializationHelper {
-    pub map: Map
+    pub map: Map,
+    pub clock: GameClock,
+    pub statistics: Statistics,
+    pub packs: PackIds
 }
 
 #[derive(PartialEq, Debug, Copy, Clone, Serialize, Deserialize)]
@@ -181,7 +250,8 @@ pub struct NaturalAttack {
     pub damage_n_dice: i32,
     pub damage_die_type: i32,
     pub damage_bonus: i32,
-    pub hit_bonus: i32
+    pub hit_bonus: i32,
+    pub range: Option<i32>
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -239,6 +309,21 @@ pub struct Door {
     pub open: bool
 }
 
+/// A door that won't open without a key with the same lock id, or a successful lockpicking check
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Locked {
+    pub lock_id: String,
+    pub difficulty: i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Key {
+    pub lock_id: String
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OpensDoors {}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Quips {
     pub available: Vec<String>
@@ -260,9 +345,9 @@ pub struct Attributes {
 }
 
 
-#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum Skill {
-    Melee, Defense, Magic
+    Melee, Defense, Magic, Stealth, Lockpicking
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
@@ -283,5 +368,149 @@ pub struct Pools {
     pub hit_points: Pool,
     pub mana: Pool,
     pub xp: i32,
-    pub level: i32
+    pub level: i32,
+    pub total_weight: f32,
+    pub gold: i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Energy {
+    pub current: i32
+}
+
+impl Energy {
+    pub fn spend(store: &mut WriteStorage<Energy>, entity: Entity, cost: i32) {
+        if let Some(energy) = store.get_mut(entity) {
+            energy.current -= cost;
+        }
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MyTurn {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct KnownSpell {
+    pub owner: Entity,
+    pub mana_cost: i32
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToCastSpell {
+    pub spell: Entity,
+    pub target: Option<Point>
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TeachesSpell {
+    pub spell: String
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct EquipmentChanged {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Overloaded {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Shield {
+    pub block_chance: i32,
+    pub damage_reduction: i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct TwoHanded {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Regeneration {
+    pub hp_turns: i32,
+    pub mana_turns: i32
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum BehaviourAction {
+    Attack,
+    Shoot,
+    Cast,
+    Chase,
+    Flee,
+    KeepDistance(i32),
+    GuardArea(i32),
+    ReturnHome,
+    Loot,
+    FollowSchedule,
+    Wander,
+    Idle,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum BehaviourCondition {
+    Always,
+    SeesEnemy,
+    EnemyWithin(i32),
+    HpBelow(i32),
+    AwayFromHome(i32),
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct BehaviourRule {
+    pub action: BehaviourAction,
+    pub condition: BehaviourCondition
+}
+
+/// Rules are tried in order each turn, and the first one that can be carried out is used
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Behaviour {
+    pub rules: Vec<BehaviourRule>
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Home {
+    pub x: i32,
+    pub y: i32
+}
+
+/// Where a monster last saw its target, and how much longer it will keep searching once it gets there
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Memory {
+    pub last_seen: Option<Point>,
+    pub search_turns: i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Asleep {}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reaction {
+    Ignore, Attack, Flee
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Faction {
+    pub name: String
+}
+
+/// The player's standing with each faction, which can override how that faction reacts to them
+#[derive(Component, Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Reputation {
+    pub standing: HashMap<String, i32>
+}
+
+/// Marks a weapon as one that's fired at range; its damage still comes from the `MeleeWeapon` stats
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct RangedWeapon {
+    pub range: i32
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToShoot {
+    pub target: Point
+}
+
+/// Packs are formed at spawn time and numbered from `PackIds`. The leader is a member of its own pack; once
+/// it dies, no member is marked as leader any more.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct PackMember {
+    pub pack: u32,
+    pub leader: bool
 }
@@ -1,12 +1,24 @@
 use bracket_lib::color::RGB;
-use bracket_lib::prelude::to_cp437;
+use bracket_lib::prelude::{to_cp437, Point};
+use bracket_lib::random::RandomNumberGenerator;
 use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
 
-use crate::components::{BlocksTile, Monster, Name, Player, Pools, Position, Renderable, SufferDamage};
-use crate::gamelog::GameLog;
+use crate::components::{Asleep, Consumable, Faction, Equipped, InBackpack, Item, KnownSpell, Name, Player, Pools, Position, ProvidesFood, Reaction, Renderable, Reputation, SerializeMe, SufferDamage};
+use crate::events::{GameEvent, GameEvents};
+use crate::gamelog::{GameLog, LogBuilder, LogCategory};
+use crate::gamesystem::{xp_for_kill, xp_for_next_level};
+use crate::map::tiletype::tile_walkable;
 use crate::map::Map;
+use crate::monster_ai_system::HOSTILE_STANDING;
+use crate::raws::rawmaster::faction_reaction;
+use crate::raws::RAWS;
 use crate::RunState;
 
+const CORPSE_WEIGHT: f32 = 20.0;
+const CORPSE_FOOD: i32 = 100;
+const ATTACK_STANDING_PENALTY: i32 = 10;
+
 pub struct DamageSystem{}
 
 impl <'a> System<'a> for DamageSystem {
@@ -16,52 +28,93 @@ impl <'a> System<'a> for DamageSystem {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, Faction>,
+        WriteStorage<'a, Reputation>,
+        WriteExpect<'a, GameEvents>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut pools, mut damage, positions, mut map, entities) = data;
+        let (mut pools, mut damage, positions, mut map, entities, player_entity, mut gamelog, mut rng, mut asleep, factions, mut reputations, mut events) = data;
 
+        let mut xp_gain = 0;
         for (entity, pools, damage) in (&entities, &mut pools, &damage).join() {
-            pools.hit_points.current -= damage.amount.iter().sum::<i32>();
+            asleep.remove(entity);
             let pos = positions.get(entity);
-            if let Some(pos) = pos {
-                map.bloodstains.insert((pos.x, pos.y));
+            for hit in damage.amount.iter() {
+                let was_alive = pools.hit_points.current > 0;
+                pools.hit_points.current -= hit.amount;
+                events.publish(GameEvent::DamageTaken { target: entity, source: hit.source, amount: hit.amount });
+                if was_alive && pools.hit_points.current < 1 {
+                    let xp = if hit.from_player { xp_for_kill(pools.level) } else { 0 };
+                    xp_gain += xp;
+                    events.publish(GameEvent::EntityDied {
+                        victim: entity,
+                        killer: hit.source,
+                        xp,
+                        position: pos.map(|p| Point::new(p.x, p.y)),
+                    });
+                }
+                if let Some(pos) = pos {
+                    splatter_blood(&mut map, &mut rng, pos, hit.amount);
+                }
+                if hit.from_player && entity != *player_entity {
+                    if let (Some(faction), Some(reputation)) = (factions.get(entity), reputations.get_mut(*player_entity)) {
+                        harm_reputation(reputation, &faction.name, &mut gamelog);
+                    }
+                }
+            }
+        }
+        damage.clear();
+
+        if xp_gain > 0 {
+            if let Some(player_pools) = pools.get_mut(*player_entity) {
+                player_pools.xp += xp_gain;
+                gamelog.add(LogCategory::System, format!("You gain {} experience.", xp_gain));
+                if player_pools.xp >= xp_for_next_level(player_pools.level) {
+                    gamelog.add(LogCategory::System, "You feel ready to grow stronger.");
+                }
             }
         }
-        damage.clear()
     }
 
 }
 
+// Hurting members of a faction that wasn't already out for blood will eventually turn it against the player
+fn harm_reputation(reputation: &mut Reputation, faction: &str, gamelog: &mut GameLog) {
+    if faction_reaction(faction, "Player", &RAWS.lock().unwrap()) == Reaction::Attack {
+        return;
+    }
+    let standing = reputation.standing.entry(faction.to_string()).or_insert(0);
+    let was_hostile = *standing <= HOSTILE_STANDING;
+    *standing -= ATTACK_STANDING_PENALTY;
+    if !was_hostile && *standing <= HOSTILE_STANDING {
+        gamelog.add(LogCategory::System, format!("The {} turn against you!", faction));
+    }
+}
+
 impl DamageSystem {
 
     pub fn delete_the_dead(ecs: &mut World) -> bool {
-        let mut dead: Vec<Entity> = Vec::new();
-        let mut pools = ecs.write_storage::<Pools>();
+        let mut dead: Vec<(Entity, String, Option<Position>)> = Vec::new();
         {
+            let pools = ecs.read_storage::<Pools>();
             let players = ecs.read_storage::<Player>();
             let entities = ecs.entities();
-            let mut names = ecs.write_storage::<Name>();
-            let mut renderables = ecs.write_storage::<Renderable>();
+            let names = ecs.read_storage::<Name>();
+            let positions = ecs.read_storage::<Position>();
             let mut gamelog = ecs.write_resource::<GameLog>();
             for (entity, pools) in (&entities, &pools).join() {
                 if pools.hit_points.current < 1 {
                     let player = players.get(entity);
                     match player {
                         None => {
-                            let name = names.get_mut(entity);
-                            if let Some(name) = name {
-                                gamelog.entries.push(format!("{} is dead", name.name));
-                                let mut corpse_name = "Remains of ".to_owned();
-                                corpse_name.push_str(&name.name);
-                                name.name = corpse_name;
-                            }
-                            dead.push(entity);
-                            let r =  renderables.get_mut(entity);
-                            if let Some(r) = r {
-                                r.glyph = to_cp437('%');
-                                r.fg = RGB::from_f32(0.75, 0., 0.);
-                            }
+                            let name = names.get(entity).map_or("Something".to_string(), |n| n.name.clone());
+                            LogBuilder::new(LogCategory::Combat).name(&name).append(" is dead").log(&mut gamelog);
+                            dead.push((entity, name, positions.get(entity).cloned()));
                         },
                         Some(_) => {
                             let mut runstate = ecs.write_resource::<RunState>();
@@ -72,13 +125,147 @@ impl DamageSystem {
                 }
             }
         }
-        let mut monsters = ecs.write_storage::<Monster>();
-        let mut blockers = ecs.write_storage::<BlocksTile>();
-        for victim in &dead {
-            monsters.remove(*victim);
-            blockers.remove(*victim);
-            pools.remove(*victim);
+        for (victim, name, pos) in dead.iter() {
+            if let Some(pos) = pos {
+                DamageSystem::drop_belongings(ecs, *victim, pos);
+                DamageSystem::spawn_corpse(ecs, name, pos);
+            }
+            DamageSystem::forget_spells(ecs, *victim);
+            ecs.delete_entity(*victim).expect("Unable to delete the dead");
         }
         !&dead.is_empty()
     }
+
+    fn drop_belongings(ecs: &mut World, victim: Entity, pos: &Position) {
+        let entities = ecs.entities();
+        let mut equipped = ecs.write_storage::<Equipped>();
+        let mut backpack = ecs.write_storage::<InBackpack>();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut to_drop: Vec<Entity> = Vec::new();
+        for (entity, eq) in (&entities, &equipped).join() {
+            if eq.owner == victim {
+                to_drop.push(entity);
+            }
+        }
+        for (entity, bp) in (&entities, &backpack).join() {
+            if bp.owner == victim {
+                to_drop.push(entity);
+            }
+        }
+        for item in to_drop.iter() {
+            equipped.remove(*item);
+            backpack.remove(*item);
+            positions.insert(*item, Position { x: pos.x, y: pos.y }).expect("Unable to drop item");
+        }
+    }
+
+    // Spells can't be dropped like belongings; they die with whoever knew them, wherever that was
+    fn forget_spells(ecs: &mut World, victim: Entity) {
+        let spells: Vec<Entity> = (&ecs.entities(), &ecs.read_storage::<KnownSpell>())
+            .join()
+            .filter(|(_, spell)| spell.owner == victim)
+            .map(|(spell_entity, _)| spell_entity)
+            .collect();
+        ecs.delete_entities(&spells).expect("Unable to delete spells");
+    }
+
+    fn spawn_corpse(ecs: &mut World, name: &String, pos: &Position) {
+        ecs.create_entity()
+            .with(Position { x: pos.x, y: pos.y })
+            .with(Renderable {
+                glyph: to_cp437('%'),
+                fg: RGB::from_f32(0.75, 0., 0.),
+                bg: RGB::from_f32(0., 0., 0.),
+                render_order: 2,
+            })
+            .with(Name { name: format!("Remains of {}", name) })
+            .with(Item { weight: CORPSE_WEIGHT, value: 0.0 })
+            .with(Consumable {})
+            .with(ProvidesFood { points: CORPSE_FOOD })
+            .marked::<SimpleMarker<SerializeMe>>()
+            .build();
+    }
+}
+
+// Bigger hits spray blood further, onto the tiles around the victim
+fn splatter_blood(map: &mut Map, rng: &mut RandomNumberGenerator, pos: &Position, amount: i32) {
+    if amount < 1 {
+        return;
+    }
+    map.bloodstains.insert((pos.x, pos.y));
+    for _ in 0..amount / 4 {
+        let x = pos.x + rng.range(-1, 2);
+        let y = pos.y + rng.range(-1, 2);
+        if map.is_tile_in_bounds(x, y) && tile_walkable(map.tiles[x as usize][y as usize]) {
+            map.bloodstains.insert((x, y));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::saveload::SimpleMarkerAllocator;
+
+    use super::*;
+    use crate::components::Pool;
+
+    fn world() -> World {
+        let mut ecs = World::new();
+        ecs.register::<Pools>();
+        ecs.register::<Player>();
+        ecs.register::<Name>();
+        ecs.register::<Position>();
+        ecs.register::<Equipped>();
+        ecs.register::<InBackpack>();
+        ecs.register::<KnownSpell>();
+        ecs.register::<Renderable>();
+        ecs.register::<Item>();
+        ecs.register::<Consumable>();
+        ecs.register::<ProvidesFood>();
+        ecs.register::<SimpleMarker<SerializeMe>>();
+        ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        ecs.insert(GameLog::new());
+        ecs.insert(RunState::AwaitingInput);
+        ecs
+    }
+
+    fn dead_caster(ecs: &mut World, position: Option<Position>) -> Entity {
+        let pool = |current| Pool { max: 10, current };
+        let mut caster = ecs
+            .create_entity()
+            .with(Name { name: "Goblin Shaman".to_string() })
+            .with(Pools { hit_points: pool(0), mana: pool(10), xp: 0, level: 1, total_weight: 0.0, gold: 0 });
+        if let Some(position) = position {
+            caster = caster.with(position);
+        }
+        caster.build()
+    }
+
+    #[test]
+    fn dead_casters_take_their_spells_with_them() {
+        let mut ecs = world();
+        let caster = dead_caster(&mut ecs, Some(Position { x: 3, y: 4 }));
+        let potion = ecs.create_entity().with(Item { weight: 0.5, value: 10.0 }).with(InBackpack { owner: caster }).build();
+        for _ in 0..2 {
+            ecs.create_entity().with(KnownSpell { owner: caster, mana_cost: 2 }).build();
+        }
+
+        assert!(DamageSystem::delete_the_dead(&mut ecs));
+        ecs.maintain();
+        assert!(!ecs.is_alive(caster));
+        assert_eq!(ecs.read_storage::<KnownSpell>().count(), 0);
+        assert_eq!(ecs.read_storage::<Position>().get(potion).map(|p| (p.x, p.y)), Some((3, 4)));
+        assert!(ecs.read_storage::<InBackpack>().get(potion).is_none());
+    }
+
+    #[test]
+    fn spells_are_forgotten_even_when_the_caster_was_nowhere() {
+        let mut ecs = world();
+        let caster = dead_caster(&mut ecs, None);
+        ecs.create_entity().with(KnownSpell { owner: caster, mana_cost: 2 }).build();
+
+        DamageSystem::delete_the_dead(&mut ecs);
+        ecs.maintain();
+        assert_eq!(ecs.read_storage::<KnownSpell>().count(), 0);
+    }
 }
@@ -0,0 +1,190 @@
+use bracket_lib::prelude::console;
+use specs::prelude::*;
+
+use crate::components::{
+    Dialogue, Energy, EquipmentChanged, InBackpack, Name, Pools, Quests, Reputation, Vendor,
+};
+use crate::energy_system::WAIT_COST;
+use crate::events::{GameEvent, GameEvents};
+use crate::gameclock::GameClock;
+use crate::gamelog::{GameLog, LogCategory};
+use crate::gui::VendorMode;
+use crate::raws::dialogue_structs::{DialogueChoice, DialogueNode};
+use crate::raws::rawmaster::{dialogue_node, dialogue_node_index, spawn_named_item, SpawnType};
+use crate::raws::RAWS;
+use crate::vendor_system::{closed_message, is_open};
+use crate::RunState;
+
+/// The choices at a node that the player meets the requirements for, as indices into `node.choices`
+pub fn available_choices(ecs: &World, node: &DialogueNode) -> Vec<usize> {
+    node.choices
+        .iter()
+        .enumerate()
+        .filter(|(_, choice)| {
+            choice
+                .requires
+                .as_ref()
+                .is_none_or(|requires| requires.iter().all(|r| requirement_met(ecs, r)))
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Carries out a choice the player made while talking to `speaker`, and decides where the
+/// conversation goes next. Finishing a conversation takes a turn.
+pub fn choose(ecs: &mut World, speaker: Entity, node: usize, choice: usize) -> RunState {
+    let tree = match ecs.read_storage::<Dialogue>().get(speaker) {
+        None => return RunState::AwaitingInput,
+        Some(dialogue) => dialogue.tree.clone(),
+    };
+    let (chosen, next): (Option<DialogueChoice>, Option<usize>) = {
+        let raws = RAWS.lock().unwrap();
+        let chosen = dialogue_node(&raws, &tree, node).and_then(|n| n.choices.get(choice)).cloned();
+        let next = chosen
+            .as_ref()
+            .and_then(|c| c.next.as_ref())
+            .and_then(|id| dialogue_node_index(&raws, &tree, id));
+        (chosen, next)
+    };
+
+    let actions = chosen.and_then(|c| c.actions).unwrap_or_default();
+    for action in actions.iter() {
+        perform(ecs, speaker, action);
+    }
+    if actions.iter().any(|a| a == "trade") {
+        let vendors = ecs.read_storage::<Vendor>();
+        match vendors.get(speaker) {
+            Some(vendor) if is_open(vendor, &ecs.fetch::<GameClock>()) => {
+                return RunState::ShowVendor { vendor: speaker, mode: VendorMode::Buy };
+            }
+            Some(vendor) => {
+                let names = ecs.read_storage::<Name>();
+                let name = names.get(speaker).map_or("The vendor", |n| n.name.as_str());
+                ecs.fetch_mut::<GameLog>().add(LogCategory::Dialogue, closed_message(vendor, name));
+            }
+            None => {}
+        }
+    }
+    match next {
+        Some(node) => RunState::ShowDialogue { speaker, node },
+        None => {
+            let player = *ecs.fetch::<Entity>();
+            Energy::spend(&mut ecs.write_storage::<Energy>(), player, WAIT_COST);
+            ecs.write_resource::<GameEvents>().publish(GameEvent::ConversationEnded);
+            RunState::PlayerTurn
+        }
+    }
+}
+
+// Requirements and actions are written as `name:argument` in the raws
+fn split_arg(s: &str) -> (&str, &str) {
+    s.split_once(':').unwrap_or((s, ""))
+}
+
+fn requirement_met(ecs: &World, requirement: &str) -> bool {
+    let player = *ecs.fetch::<Entity>();
+    let quests = ecs.read_storage::<Quests>();
+    let quests = quests.get(player);
+    let (name, arg) = split_arg(requirement);
+    match name {
+        "gold" => ecs.read_storage::<Pools>().get(player).map_or(0, |p| p.gold) >= arg.parse().unwrap_or(0),
+        "has_item" => carried_item(ecs, player, arg).is_some(),
+        "standing" => {
+            let (faction, minimum) = split_arg(arg);
+            let standing = ecs
+                .read_storage::<Reputation>()
+                .get(player)
+                .and_then(|r| r.standing.get(faction).copied())
+                .unwrap_or(0);
+            standing >= minimum.parse().unwrap_or(0)
+        }
+        "quest_active" => quests.is_some_and(|q| q.active.contains(arg)),
+        "quest_done" => quests.is_some_and(|q| q.completed.contains(arg)),
+        "quest_new" => quests.is_none_or(|q| !q.active.contains(arg) && !q.completed.contains(arg)),
+        _ => {
+            console::log(format!("Warning: dialogue requirement {} not implemented!", name));
+            false
+        }
+    }
+}
+
+// Items are handed out by raw id, but taken back by the name the player sees
+fn perform(ecs: &mut World, speaker: Entity, action: &str) {
+    let player = *ecs.fetch::<Entity>();
+    let speaker_name = ecs
+        .read_storage::<Name>()
+        .get(speaker)
+        .map_or("Someone".to_string(), |n| n.name.clone());
+    let (name, arg) = split_arg(action);
+    let message = match name {
+        "give_item" => {
+            let given = spawn_named_item(&RAWS.lock().unwrap(), &arg.to_string(), SpawnType::Carried { by: player }, ecs);
+            given.map(|item| {
+                let item_name = ecs.read_storage::<Name>().get(item).map_or(arg.to_string(), |n| n.name.clone());
+                format!("{} gives you the {}.", speaker_name, item_name)
+            })
+        }
+        "take_item" => carried_item(ecs, player, arg).map(|item| {
+            ecs.delete_entity(item).expect("Unable to delete item");
+            format!("You hand over the {}.", arg)
+        }),
+        "give_gold" | "take_gold" => {
+            let amount: i32 = arg.parse().unwrap_or(0);
+            let mut pools = ecs.write_storage::<Pools>();
+            pools.get_mut(player).map(|p| {
+                if name == "give_gold" {
+                    p.gold += amount;
+                    format!("{} gives you {} gold.", speaker_name, amount)
+                } else {
+                    p.gold -= amount;
+                    format!("You pay {} gold.", amount)
+                }
+            })
+        }
+        "heal" => ecs.write_storage::<Pools>().get_mut(player).map(|p| {
+            p.hit_points.current = p.hit_points.max;
+            "You feel restored.".to_string()
+        }),
+        "start_quest" => ecs.write_storage::<Quests>().get_mut(player).map(|q| {
+            q.active.insert(arg.to_string());
+            format!("New quest: {}", arg)
+        }),
+        "complete_quest" => ecs.write_storage::<Quests>().get_mut(player).map(|q| {
+            q.active.remove(arg);
+            q.completed.insert(arg.to_string());
+            format!("Quest complete: {}", arg)
+        }),
+        // Handled by `choose`, since it moves the player on to the trade screen
+        "trade" => None,
+        "standing" => {
+            let (faction, change) = split_arg(arg);
+            let mut reputations = ecs.write_storage::<Reputation>();
+            if let Some(reputation) = reputations.get_mut(player) {
+                *reputation.standing.entry(faction.to_string()).or_insert(0) += change.parse::<i32>().unwrap_or(0);
+            }
+            None
+        }
+        _ => {
+            console::log(format!("Warning: dialogue action {} not implemented!", name));
+            None
+        }
+    };
+    if name == "give_item" || name == "take_item" {
+        ecs.write_storage::<EquipmentChanged>()
+            .insert(player, EquipmentChanged {})
+            .expect("Unable to insert marker");
+    }
+    if let Some(message) = message {
+        ecs.fetch_mut::<GameLog>().add(LogCategory::Dialogue, message);
+    }
+}
+
+fn carried_item(ecs: &World, owner: Entity, item_name: &str) -> Option<Entity> {
+    let entities = ecs.entities();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let names = ecs.read_storage::<Name>();
+    (&entities, &backpack, &names)
+        .join()
+        .find(|(_, carried, name)| carried.owner == owner && name.name == item_name)
+        .map(|(entity, _, _)| entity)
+}
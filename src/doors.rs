@@ -0,0 +1,53 @@
+use bracket_lib::prelude::to_cp437;
+use specs::prelude::*;
+
+use crate::components::{BlocksTile, BlocksVisibility, Door, Renderable};
+use crate::map::Map;
+
+/// Everything that changes when a door swings open or shut
+pub type DoorStorages<'a> = (
+    WriteStorage<'a, Door>,
+    WriteStorage<'a, BlocksTile>,
+    WriteStorage<'a, BlocksVisibility>,
+    WriteStorage<'a, Renderable>,
+);
+
+pub fn door_at(map: &Map, (doors, ..): &DoorStorages, x: i32, y: i32) -> Option<Entity> {
+    map.tile_content[x as usize][y as usize]
+        .iter()
+        .find(|e| doors.get(**e).is_some())
+        .copied()
+}
+
+/// Doors can't be shut on anything standing or lying in the doorway
+pub fn doorway_clear(map: &Map, door: Entity, x: i32, y: i32) -> bool {
+    map.tile_content[x as usize][y as usize].iter().all(|e| *e == door)
+}
+
+/// Opens or shuts a door. The map is updated straight away, rather than waiting for the indexing
+/// and visibility systems to catch up, so that anyone moving later this turn sees the change.
+pub fn set_door(
+    map: &mut Map,
+    (doors, blocks_tile, blocks_visibility, renderables): &mut DoorStorages,
+    door: Entity,
+    x: i32,
+    y: i32,
+    open: bool,
+) {
+    if let Some(d) = doors.get_mut(door) {
+        d.open = open;
+    }
+    if open {
+        blocks_tile.remove(door);
+        blocks_visibility.remove(door);
+        map.view_blocked.remove(&(x, y));
+    } else {
+        blocks_tile.insert(door, BlocksTile {}).expect("Unable to insert blocker");
+        blocks_visibility.insert(door, BlocksVisibility {}).expect("Unable to insert blocker");
+        map.view_blocked.insert((x, y));
+    }
+    map.blocked[x as usize][y as usize] = !open;
+    if let Some(glyph) = renderables.get_mut(door) {
+        glyph.glyph = to_cp437(if open { '/' } else { '+' });
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use specs::prelude::*;
+
+use crate::components::{Attributes, EquipmentChanged, Equipped, InBackpack, Item, Overloaded, Pools};
+use crate::gamelog::{GameLog, LogCategory};
+use crate::gamesystem::carry_capacity;
+
+pub const OVERLOADED_ENERGY_PENALTY: i32 = 3;
+pub const OVERLOADED_COMBAT_PENALTY: i32 = 2;
+
+pub struct EncumbranceSystem {}
+
+impl<'a> System<'a> for EncumbranceSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, EquipmentChanged>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Pools>,
+        WriteStorage<'a, Overloaded>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut gamelog,
+            mut equip_dirty,
+            items,
+            backpacks,
+            wielded,
+            attributes,
+            mut pools,
+            mut overloaded
+        ) = data;
+
+        if equip_dirty.is_empty() {
+            return;
+        }
+
+        let mut to_update: HashMap<Entity, f32> = HashMap::new();
+        for (entity, _dirty) in (&entities, &equip_dirty).join() {
+            to_update.insert(entity, 0.0);
+        }
+        equip_dirty.clear();
+
+        for (item, equipped) in (&items, &wielded).join() {
+            if let Some(weight) = to_update.get_mut(&equipped.owner) {
+                *weight += item.weight;
+            }
+        }
+        for (item, carried) in (&items, &backpacks).join() {
+            if let Some(weight) = to_update.get_mut(&carried.owner) {
+                *weight += item.weight;
+            }
+        }
+
+        for (entity, weight) in to_update.iter() {
+            if let Some(pools) = pools.get_mut(*entity) {
+                pools.total_weight = *weight;
+            }
+            let capacity = match attributes.get(*entity) {
+                None => continue,
+                Some(attrs) => carry_capacity(attrs.might.base + attrs.might.modifiers),
+            };
+            let was_overloaded = overloaded.get(*entity).is_some();
+            if *weight > capacity {
+                overloaded.insert(*entity, Overloaded {}).expect("Unable to insert overloaded");
+                if !was_overloaded && *entity == *player_entity {
+                    gamelog.add(LogCategory::System, "You are overloaded, and struggle to move and fight.");
+                }
+            } else {
+                overloaded.remove(*entity);
+                if was_overloaded && *entity == *player_entity {
+                    gamelog.add(LogCategory::System, "You are no longer overloaded.");
+                }
+            }
+        }
+    }
+}
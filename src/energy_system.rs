@@ -0,0 +1,103 @@
+use specs::prelude::*;
+
+use crate::components::{Attributes, Energy, HungerClock, HungerState, MyTurn, Overloaded};
+use crate::encumbrance_system::OVERLOADED_ENERGY_PENALTY;
+use crate::gameclock::GameClock;
+use crate::RunState;
+
+pub const ACTION_THRESHOLD: i32 = 100;
+pub const BASE_ENERGY_GAIN: i32 = 10;
+
+pub const MOVE_COST: i32 = 100;
+pub const MELEE_COST: i32 = 110;
+pub const SHOOT_COST: i32 = 120;
+pub const USE_ITEM_COST: i32 = 120;
+pub const PICK_UP_COST: i32 = 50;
+pub const DROP_COST: i32 = 50;
+pub const UNEQUIP_COST: i32 = 80;
+pub const WAIT_COST: i32 = 100;
+
+pub struct EnergySystem {}
+
+impl EnergySystem {
+    pub fn energy_gain(attributes: Option<&Attributes>, hunger: Option<&HungerClock>, overloaded: bool) -> i32 {
+        let mut gain = BASE_ENERGY_GAIN;
+        if let Some(attributes) = attributes {
+            gain += attributes.quickness.bonus;
+        }
+        if let Some(hunger) = hunger {
+            match hunger.state {
+                HungerState::WellFed => gain += 1,
+                HungerState::Starving => gain -= 2,
+                _ => {}
+            }
+        }
+        if overloaded {
+            gain -= OVERLOADED_ENERGY_PENALTY;
+        }
+        i32::max(1, gain)
+    }
+}
+
+impl<'a> System<'a> for EnergySystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, MyTurn>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Overloaded>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, RunState>,
+        WriteExpect<'a, GameClock>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut energies,
+            mut turns,
+            attributes,
+            hunger_clocks,
+            overloaded,
+            player_entity,
+            mut runstate,
+            mut clock
+        ) = data;
+
+        turns.clear();
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+        // Without energy the player would never get a turn back, so hand control over directly
+        // and a player who hasn't spent their turn yet keeps it
+        match energies.get(*player_entity) {
+            None => {
+                *runstate = RunState::AwaitingInput;
+                return;
+            }
+            Some(energy) if energy.current >= ACTION_THRESHOLD => {
+                *runstate = RunState::AwaitingInput;
+                return;
+            }
+            _ => {}
+        }
+
+        clock.tick();
+        for (entity, energy) in (&entities, &mut energies).join() {
+            let gain = EnergySystem::energy_gain(
+                attributes.get(entity),
+                hunger_clocks.get(entity),
+                overloaded.get(entity).is_some()
+            );
+            energy.current = i32::min(energy.current + gain, ACTION_THRESHOLD);
+            if energy.current >= ACTION_THRESHOLD {
+                if entity == *player_entity {
+                    *runstate = RunState::AwaitingInput;
+                } else {
+                    turns.insert(entity, MyTurn {}).expect("Unable to insert turn marker");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use bracket_lib::prelude::Point;
+use specs::prelude::*;
+
+/// Something that happened this turn that code outside the system responsible might care about
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    DamageTaken { target: Entity, source: Option<Entity>, amount: i32 },
+    EntityDied { victim: Entity, killer: Option<Entity>, xp: i32, position: Option<Point> },
+    ItemPickedUp { item: Entity, by: Entity },
+    ItemUsed { item: Entity, by: Entity },
+    TrapTriggered { trap: Entity, victim: Entity, position: Point },
+    LevelEntered { depth: i32, position: Point },
+    DoorOpened { by: Entity, position: Point },
+    LockPicked { by: Entity, success: bool, position: Point },
+    ItemBought { price: i32 },
+    ItemSold { price: i32 },
+    ConversationEnded,
+}
+
+impl GameEvent {
+    /// Where on the map the event happened, for those that happened somewhere in particular
+    pub fn position(&self) -> Option<Point> {
+        match self {
+            GameEvent::EntityDied { position, .. } => *position,
+            GameEvent::TrapTriggered { position, .. }
+            | GameEvent::LevelEntered { position, .. }
+            | GameEvent::DoorOpened { position, .. }
+            | GameEvent::LockPicked { position, .. } => Some(*position),
+            _ => None,
+        }
+    }
+}
+
+/// Systems publish events here as they happen. Subscribers run at the end of each pass of the
+/// systems and read everything published since the previous pass, after which the events are gone.
+#[derive(Default)]
+pub struct GameEvents {
+    events: Vec<GameEvent>,
+}
+
+impl GameEvents {
+    pub fn publish(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &GameEvent> {
+        self.events.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashSet;
+
+use bracket_lib::algorithm_traits::{BaseMap, SmallVec};
+use bracket_lib::prelude::{DijkstraMap, DistanceAlg, Point};
+use specs::prelude::*;
+
+use crate::components::{BlocksTile, Item, Pools, Position};
+use crate::map::tiletype::{tile_cost, tile_walkable};
+use crate::map::Map;
+
+/// Flow maps don't look further than this, anything beyond it is treated as unreachable
+pub const FLOW_MAP_DEPTH: f32 = 200.0;
+// Scaling the distances past -1 makes fleeing monsters prefer escape routes over dead ends
+const FLEE_WEIGHT: f32 = -1.2;
+
+/// Dijkstra maps shared by all of the AI. Follow the lowest neighbouring value to move toward the
+/// player, away from the player, or toward the nearest item lying on the floor.
+pub struct FlowMaps {
+    pub to_player: DijkstraMap,
+    pub from_player: DijkstraMap,
+    pub to_items: DijkstraMap,
+    built_for: Option<FlowMapInputs>,
+}
+
+impl Default for FlowMaps {
+    fn default() -> Self {
+        FlowMaps {
+            to_player: DijkstraMap::new_empty(1, 1, FLOW_MAP_DEPTH),
+            from_player: DijkstraMap::new_empty(1, 1, FLOW_MAP_DEPTH),
+            to_items: DijkstraMap::new_empty(1, 1, FLOW_MAP_DEPTH),
+            built_for: None,
+        }
+    }
+}
+
+// Everything the maps depend on; they're only rebuilt when one of these changes
+#[derive(PartialEq)]
+struct FlowMapInputs {
+    depth: i32,
+    player: usize,
+    impassable: Vec<usize>,
+    items: Vec<usize>,
+}
+
+/// Creatures move around too much to be worth routing around, so only the terrain and whichever
+/// blocking props the caller can't get past are considered
+pub struct TerrainView<'a> {
+    map: &'a Map,
+    impassable: &'a HashSet<usize>,
+}
+
+impl<'a> TerrainView<'a> {
+    pub fn new(map: &'a Map, impassable: &'a HashSet<usize>) -> Self {
+        TerrainView { map, impassable }
+    }
+}
+
+impl<'a> BaseMap for TerrainView<'a> {
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        let mut exits = SmallVec::new();
+        let x = idx as i32 % self.map.width;
+        let y = idx as i32 / self.map.width;
+        let cost = tile_cost(self.map.tiles[x as usize][y as usize]);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)] {
+            let (ex, ey) = (x + dx, y + dy);
+            if !self.map.is_tile_in_bounds(ex, ey) || !tile_walkable(self.map.tiles[ex as usize][ey as usize]) {
+                continue;
+            }
+            let exit = self.map.xy_idx(ex, ey);
+            if self.impassable.contains(&exit) {
+                continue;
+            }
+            exits.push((exit, if dx != 0 && dy != 0 { cost * 1.45 } else { cost }));
+        }
+        exits
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        let w = self.map.width as usize;
+        DistanceAlg::Pythagoras.distance2d(Point::new(idx1 % w, idx1 / w), Point::new(idx2 % w, idx2 / w))
+    }
+}
+
+pub struct FlowMapSystem {}
+
+impl<'a> System<'a> for FlowMapSystem {
+    type SystemData = (
+        WriteExpect<'a, FlowMaps>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Item>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut flow_maps, map, player_pos, positions, blockers, pools, items) = data;
+
+        let mut impassable: Vec<usize> = (&positions, &blockers, !&pools)
+            .join()
+            .map(|(pos, _, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        impassable.sort_unstable();
+        let mut item_tiles: Vec<usize> = (&positions, &items)
+            .join()
+            .map(|(pos, _)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        item_tiles.sort_unstable();
+        item_tiles.dedup();
+        let inputs = FlowMapInputs {
+            depth: map.depth,
+            player: map.xy_idx(player_pos.x, player_pos.y),
+            impassable,
+            items: item_tiles,
+        };
+        if flow_maps.built_for.as_ref() == Some(&inputs) {
+            return;
+        }
+
+        // Closed doors count as impassable here, so nothing tries to flow through them
+        let impassable: HashSet<usize> = inputs.impassable.iter().copied().collect();
+        let view = TerrainView::new(&map, &impassable);
+        let to_player = DijkstraMap::new(map.width, map.height, &[inputs.player], &view, FLOW_MAP_DEPTH);
+        let flee_starts: Vec<(usize, f32)> = to_player
+            .map
+            .iter()
+            .enumerate()
+            .filter(|(_, distance)| **distance < FLOW_MAP_DEPTH)
+            .map(|(idx, distance)| (idx, distance * FLEE_WEIGHT))
+            .collect();
+        let mut from_player = DijkstraMap::new_empty(map.width, map.height, FLOW_MAP_DEPTH);
+        for (idx, value) in flee_starts.iter() {
+            from_player.map[*idx] = *value;
+        }
+        DijkstraMap::build_weighted(&mut from_player, &flee_starts, &view);
+        let to_items = DijkstraMap::new(map.width, map.height, &inputs.items, &view, FLOW_MAP_DEPTH);
+
+        flow_maps.to_player = to_player;
+        flow_maps.from_player = from_player;
+        flow_maps.to_items = to_items;
+        flow_maps.built_for = Some(inputs);
+    }
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+const TICKS_PER_MINUTE: i32 = 10;
+const MINUTES_PER_DAY: i32 = 24 * 60;
+const STARTING_HOUR: i32 = 8;
+
+/// Time of day, advanced each time energy is handed out, so that a minute passes for every
+/// ordinary turn the player takes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct GameClock {
+    pub ticks: i32
+}
+
+impl GameClock {
+    pub fn new() -> Self {
+        GameClock { ticks: STARTING_HOUR * 60 * TICKS_PER_MINUTE }
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    fn minutes(&self) -> i32 {
+        self.ticks / TICKS_PER_MINUTE
+    }
+
+    /// Ordinary player turns since the game began
+    pub fn turn(&self) -> i32 {
+        self.minutes() - STARTING_HOUR * 60
+    }
+
+    pub fn day(&self) -> i32 {
+        self.minutes() / MINUTES_PER_DAY + 1
+    }
+
+    pub fn hour(&self) -> i32 {
+        (self.minutes() % MINUTES_PER_DAY) / 60
+    }
+
+    pub fn minute(&self) -> i32 {
+        self.minutes() % 60
+    }
+}
+
+/// Whether `hour` falls within a span of hours that may wrap past midnight
+pub fn hour_in_span(hour: i32, from: i32, to: i32) -> bool {
+    if from <= to {
+        hour >= from && hour < to
+    } else {
+        hour >= from || hour < to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advance(clock: &mut GameClock, minutes: i32) {
+        for _ in 0..minutes * TICKS_PER_MINUTE {
+            clock.tick();
+        }
+    }
+
+    #[test]
+    fn starts_on_the_morning_of_day_one() {
+        let clock = GameClock::new();
+        assert_eq!((clock.day(), clock.hour(), clock.minute(), clock.turn()), (1, STARTING_HOUR, 0, 0));
+    }
+
+    #[test]
+    fn a_minute_passes_every_few_ticks() {
+        let mut clock = GameClock::new();
+        for _ in 0..TICKS_PER_MINUTE - 1 {
+            clock.tick();
+        }
+        assert_eq!((clock.minute(), clock.turn()), (0, 0));
+        clock.tick();
+        assert_eq!((clock.minute(), clock.turn()), (1, 1));
+    }
+
+    #[test]
+    fn rolls_over_into_the_next_day_at_midnight() {
+        let mut clock = GameClock::new();
+        advance(&mut clock, (24 - STARTING_HOUR) * 60 - 1);
+        assert_eq!((clock.day(), clock.hour(), clock.minute()), (1, 23, 59));
+        advance(&mut clock, 1);
+        assert_eq!((clock.day(), clock.hour(), clock.minute()), (2, 0, 0));
+        assert_eq!(clock.turn(), (24 - STARTING_HOUR) * 60);
+    }
+
+    #[test]
+    fn spans_of_hours_can_wrap_past_midnight() {
+        assert!(hour_in_span(9, 9, 17));
+        assert!(!hour_in_span(17, 9, 17));
+        assert!(hour_in_span(23, 22, 6));
+        assert!(hour_in_span(0, 22, 6));
+        assert!(!hour_in_span(6, 22, 6));
+        assert!(!hour_in_span(12, 22, 6));
+    }
+}
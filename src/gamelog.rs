@@ -1,3 +1,133 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+
+use bracket_lib::color::{CYAN, GOLD, RED, RGB, WHITE, YELLOW};
+
+/// Older entries are dropped once the log holds this many
+pub const MAX_LOG_ENTRIES: usize = 1000;
+pub const LOG_EXPORT_FILE: &str = "./game_log.txt";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LogCategory {
+    Combat,
+    Loot,
+    Dialogue,
+    System,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 4] =
+        [LogCategory::Combat, LogCategory::Loot, LogCategory::Dialogue, LogCategory::System];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Dialogue => "Dialogue",
+            LogCategory::System => "System",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogSpan {
+    pub text: String,
+    pub colour: RGB,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub turn: i32,
+    pub category: LogCategory,
+    pub spans: Vec<LogSpan>,
+}
+
+impl LogEntry {
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+/// Puts a log entry together a piece at a time, so that names and numbers can stand out
+pub struct LogBuilder {
+    category: LogCategory,
+    spans: Vec<LogSpan>,
+}
+
+impl LogBuilder {
+    pub fn new(category: LogCategory) -> Self {
+        LogBuilder { category, spans: Vec::new() }
+    }
+
+    pub fn colour<S: ToString>(mut self, text: S, colour: RGB) -> Self {
+        self.spans.push(LogSpan { text: text.to_string(), colour });
+        self
+    }
+
+    pub fn append<S: ToString>(self, text: S) -> Self {
+        self.colour(text, RGB::named(WHITE))
+    }
+
+    /// Creatures and people
+    pub fn name<S: ToString>(self, text: S) -> Self {
+        self.colour(text, RGB::named(YELLOW))
+    }
+
+    pub fn item<S: ToString>(self, text: S) -> Self {
+        self.colour(text, RGB::named(CYAN))
+    }
+
+    pub fn damage(self, amount: i32) -> Self {
+        self.colour(amount, RGB::named(RED))
+    }
+
+    pub fn gold(self, amount: i32) -> Self {
+        self.colour(amount, RGB::named(GOLD))
+    }
+
+    pub fn log(self, log: &mut GameLog) {
+        log.push(self.category, self.spans);
+    }
+}
+
 pub struct GameLog {
-    pub entries: Vec<String>
+    entries: VecDeque<LogEntry>,
+    /// Stamped on each new entry; kept in step with the game clock by the main loop
+    pub turn: i32,
+}
+
+impl GameLog {
+    pub fn new() -> Self {
+        GameLog { entries: VecDeque::new(), turn: 0 }
+    }
+
+    /// Logs a message in a single colour
+    pub fn add<S: ToString>(&mut self, category: LogCategory, text: S) {
+        LogBuilder::new(category).append(text).log(self);
+    }
+
+    fn push(&mut self, category: LogCategory, spans: Vec<LogSpan>) {
+        self.entries.push_back(LogEntry { turn: self.turn, category, spans });
+        while self.entries.len() > MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Entries from oldest to newest, optionally only those in one category
+    pub fn entries(&self, filter: Option<LogCategory>) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.entries.iter().filter(move |e| filter.is_none_or(|c| e.category == c))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn export(&self, path: &str) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        for entry in self.entries.iter() {
+            writeln!(file, "[{:>6}] {:<8} {}", entry.turn, entry.category.name(), entry.text())?;
+        }
+        Ok(())
+    }
 }
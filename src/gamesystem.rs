@@ -1,4 +1,4 @@
-use crate::components::{Skill, Skills};
+use crate::components::{HungerState, Skill, Skills};
 
 pub fn attr_bonus(value: i32) -> i32 {
     (value-10)/2
@@ -31,3 +31,100 @@ pub fn mana_at_level(intelligence: i32, level: i32) -> i32 {
 pub fn skill_bonus(skill: Skill, skills: &Skills) -> i32 {
     *skills.skills.get(&skill).unwrap_or(&-4)
 }
+
+pub fn xp_for_kill(victim_level: i32) -> i32 {
+    100 * victim_level
+}
+
+pub fn xp_for_next_level(level: i32) -> i32 {
+    level * 1000
+}
+
+pub fn spell_difficulty(mana_cost: i32) -> i32 {
+    4 + mana_cost / 2
+}
+
+pub fn spell_power_bonus(magic_skill: i32, intelligence_bonus: i32) -> i32 {
+    i32::max(0, magic_skill + intelligence_bonus)
+}
+
+pub fn carry_capacity(might: i32) -> f32 {
+    (i32::max(1, might) * 15) as f32
+}
+
+pub fn block_chance(shield_chance: i32, defense_skill: i32) -> i32 {
+    i32::min(75, i32::max(0, shield_chance + defense_skill * 5))
+}
+
+pub fn block_reduction(shield_reduction: i32, defense_skill: i32) -> i32 {
+    i32::max(0, shield_reduction + defense_skill)
+}
+
+fn hunger_regen_modifier(hunger: HungerState) -> Option<i32> {
+    match hunger {
+        HungerState::WellFed => Some(-3),
+        HungerState::Normal => Some(0),
+        HungerState::Hungry => Some(5),
+        HungerState::Starving => None,
+    }
+}
+
+pub fn hp_regen_interval(fitness_bonus: i32, hunger: HungerState) -> Option<i32> {
+    hunger_regen_modifier(hunger).map(|modifier| i32::max(2, 10 - fitness_bonus + modifier))
+}
+
+pub fn mana_regen_interval(intelligence_bonus: i32, magic_skill: i32, hunger: HungerState) -> Option<i32> {
+    hunger_regen_modifier(hunger).map(|modifier| i32::max(2, 10 - intelligence_bonus - magic_skill + modifier))
+}
+
+/// How far the sound of someone moving carries, given the weight of the armour they have on
+pub fn movement_noise(armour_weight: f32, stealth_bonus: i32) -> i32 {
+    i32::max(1, 3 + (armour_weight / 5.0) as i32 - stealth_bonus)
+}
+
+/// What a vendor charges for an item, or offers for it, given the player's standing with their faction
+pub fn trade_price(value: f32, standing: i32, buying: bool) -> i32 {
+    let disposition = i32::min(50, i32::max(-50, standing)) as f32 / 100.0;
+    let price = if buying {
+        value * (1.2 - disposition * 0.5)
+    } else {
+        value * (0.5 + disposition * 0.5)
+    };
+    i32::max(1, price.round() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_vendors_mark_up_and_down_from_the_value() {
+        assert_eq!(trade_price(100.0, 0, true), 120);
+        assert_eq!(trade_price(100.0, 0, false), 50);
+    }
+
+    #[test]
+    fn standing_moves_prices_and_is_capped() {
+        assert_eq!(trade_price(100.0, 50, true), 95);
+        assert_eq!(trade_price(100.0, 50, false), 75);
+        assert_eq!(trade_price(100.0, -50, true), 145);
+        assert_eq!(trade_price(100.0, -50, false), 25);
+        assert_eq!(trade_price(100.0, 500, true), trade_price(100.0, 50, true));
+        assert_eq!(trade_price(100.0, -500, false), trade_price(100.0, -50, false));
+    }
+
+    #[test]
+    fn nothing_trades_for_less_than_a_gold_piece() {
+        assert_eq!(trade_price(0.5, 0, false), 1);
+        assert_eq!(trade_price(0.0, -50, true), 1);
+    }
+
+    #[test]
+    fn selling_never_pays_more_than_buying_costs() {
+        for standing in -60..=60 {
+            for value in [1.0, 7.5, 30.0, 250.0].iter() {
+                assert!(trade_price(*value, standing, false) <= trade_price(*value, standing, true));
+            }
+        }
+    }
+}
@@ -1,21 +1,35 @@
 use bracket_lib::color::{
-    BLACK, BLUE, CYAN, GREEN, GREY, MAGENTA, ORANGE, RED, RGB, WHEAT, WHITE, YELLOW,
+    BLACK, BLUE, CYAN, GOLD, GREEN, GREY, MAGENTA, ORANGE, RED, RGB, WHEAT, WHITE, YELLOW,
 };
 use bracket_lib::prelude::{
-    letter_to_option, to_cp437, BTerm, DijkstraMap, DistanceAlg, Point, VirtualKeyCode,
+    letter_to_option, to_cp437, BTerm, DijkstraMap, DistanceAlg, Point,
 };
 use bracket_lib::terminal::FontCharType;
 use specs::prelude::*;
 
 use crate::camera::{get_screen_bounds, VIEWPORT_X, VIEWPORT_Y};
 use crate::components::{
-    Attribute, Attributes, Consumable, Equipped, Hidden, HungerClock, HungerState, InBackpack,
-    Name, Player, Pools, Position, Viewshed,
+    Asleep, Attribute, Attributes, Confusion, Consumable, Dialogue, EquipmentSlot, Equipped,
+    Examinable, Hidden, HungerClock, HungerState, InBackpack, KnownSpell, MeleeWeapon, Name,
+    Overloaded, Player, Pools, Position, RangedWeapon, Skill, Skills, Vendor, Viewshed, Wearable,
 };
-use crate::gamelog::GameLog;
-use crate::map::Map;
+use crate::dialogue::available_choices;
+use crate::encumbrance_system::OVERLOADED_COMBAT_PENALTY;
+use crate::gameclock::GameClock;
+use crate::gamelog::{GameLog, LogCategory, LogEntry};
+use crate::gamesystem::{
+    carry_capacity, hp_regen_interval, mana_at_level, mana_regen_interval, player_hp_at_level, skill_bonus,
+    trade_price, xp_for_next_level,
+};
+use crate::keybindings::{Action, BindingContext, KeyBindings};
+use crate::map::{tile_name, Map};
+use crate::melee_combat_system::{armor_class_sources, hit_bonus_sources, unarmed_weapon};
+use crate::raws::rawmaster::{dialogue_node, item_name_and_value};
+use crate::raws::RAWS;
 use crate::rex_assets::RexAssets;
 use crate::saveload_system::does_save_exist;
+use crate::statistics_system::Statistics;
+use crate::vendor_system::{item_value, vendor_standing};
 use crate::{RunState, State, DEBUGGING, SCREEN_X, SCREEN_Y};
 
 const GUIHEIGHT: usize = 6;
@@ -69,9 +83,9 @@ pub fn dwaw_ui(ecs: &World, ctx: &mut BTerm) {
     }
 
     let mut y = GUIY + 1;
-    for s in log.entries.iter().rev() {
+    for entry in log.entries(None).rev() {
         if y < GUIY + GUIHEIGHT {
-            ctx.print(2, y, s);
+            print_log_entry(ctx, 2, y as i32, entry, GUIWIDTH);
         }
         y += 1;
     }
@@ -90,6 +104,38 @@ pub fn dwaw_ui(ecs: &World, ctx: &mut BTerm) {
     draw_tooltips(ecs, ctx);
 }
 
+fn attribute_summary(att: &Attributes) -> String {
+    let mut s = "".to_string();
+    if att.might.bonus < 0 {
+        s += "Weak. "
+    };
+    if att.might.bonus > 0 {
+        s += "Strong. "
+    };
+    if att.quickness.bonus < 0 {
+        s += "Clumsy. "
+    };
+    if att.quickness.bonus > 0 {
+        s += "Agile. "
+    };
+    if att.fitness.bonus < 0 {
+        s += "Unhealthy. "
+    };
+    if att.fitness.bonus > 0 {
+        s += "Healthy. "
+    };
+    if att.intelligence.bonus < 0 {
+        s += "Dumb. "
+    };
+    if att.intelligence.bonus > 0 {
+        s += "Smart. "
+    };
+    if s.is_empty() {
+        s = "Pretty Average".to_string();
+    }
+    s
+}
+
 struct Tooltip {
     lines: Vec<String>,
 }
@@ -157,35 +203,7 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut BTerm) {
             // Attributes on tooltip
             let att = attrs.get(entity);
             if let Some(att) = att {
-                let mut s = "".to_string();
-                if att.might.bonus < 0 {
-                    s += "Weak. "
-                };
-                if att.might.bonus > 0 {
-                    s += "Strong. "
-                };
-                if att.quickness.bonus < 0 {
-                    s += "Clumsy. "
-                };
-                if att.quickness.bonus > 0 {
-                    s += "Agile. "
-                };
-                if att.fitness.bonus < 0 {
-                    s += "Unhealthy. "
-                };
-                if att.fitness.bonus > 0 {
-                    s += "Healthy. "
-                };
-                if att.intelligence.bonus < 0 {
-                    s += "Dumb. "
-                };
-                if att.intelligence.bonus > 0 {
-                    s += "Smart. "
-                };
-                if s.is_empty() {
-                    s = "Pretty Average".to_string();
-                }
-                tip.add(s);
+                tip.add(attribute_summary(att));
             }
 
             // Pools on tooltip
@@ -277,7 +295,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
         y + count as i32 + 1,
         RGB::named(YELLOW),
         RGB::named(BLACK),
-        "ESCAPE to cancel",
+        format!("{} to cancel", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
@@ -314,7 +332,7 @@ pub fn show_inventory(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
         j += 1;
     }
 
-    capture_item_options_selection(ctx, equippable, count as i32)
+    capture_item_options_selection(ctx, &gs.ecs.fetch::<KeyBindings>(), equippable, count as i32)
 }
 
 pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
@@ -349,7 +367,7 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
         y + count as i32 + 1,
         RGB::named(YELLOW),
         RGB::named(BLACK),
-        "ESCAPE to cancel",
+        format!("{} to cancel", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
@@ -366,19 +384,17 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Optio
 
     match ctx.key {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = letter_to_option(key);
-                if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
-                }
-                (ItemMenuResult::NoResponse, None)
+        Some(_) if gs.ecs.fetch::<KeyBindings>().pressed(Action::MenuCancel, ctx) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return (
+                    ItemMenuResult::Selected,
+                    Some(equippable[selection as usize]),
+                );
             }
-        },
+            (ItemMenuResult::NoResponse, None)
+        }
     }
 }
 
@@ -515,18 +531,18 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
             "Quit",
         );
 
-        match ctx.key {
+        match gs.ecs.fetch::<KeyBindings>().action(BindingContext::Menu, ctx) {
             None => {
                 return MainMenuResult::NoSelection {
                     selected: selection,
                 }
             }
-            Some(key) => {
-                return match key {
-                    VirtualKeyCode::Escape => MainMenuResult::Selected {
+            Some(action) => {
+                return match action {
+                    Action::MenuCancel => MainMenuResult::Selected {
                         selected: MainMenuSelection::Quit,
                     },
-                    VirtualKeyCode::Up => {
+                    Action::MenuUp => {
                         let mut new_selection;
                         match selection {
                             MainMenuSelection::NewGame => new_selection = MainMenuSelection::Quit,
@@ -542,7 +558,7 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
                             selected: new_selection,
                         }
                     }
-                    VirtualKeyCode::Down => {
+                    Action::MenuDown => {
                         let mut new_selection;
                         match selection {
                             MainMenuSelection::NewGame => {
@@ -558,7 +574,7 @@ pub fn main_menu(gs: &mut State, ctx: &mut BTerm) -> MainMenuResult {
                             selected: new_selection,
                         }
                     }
-                    VirtualKeyCode::Return => MainMenuResult::Selected {
+                    Action::MenuSelect => MainMenuResult::Selected {
                         selected: selection,
                     },
                     _ => MainMenuResult::NoSelection {
@@ -635,6 +651,13 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     ctx.set(x_pos + name_len as i32, 0, box_gray, black, to_cp437('├'));
     ctx.print_color(x_pos + 1, 0, white, black, &map.name);
 
+    // Time of day
+    let clock = ecs.fetch::<GameClock>();
+    let time = format!("Day {}, {:02}:{:02}", clock.day(), clock.hour(), clock.minute());
+    ctx.set(VIEWPORT_X + 1, 0, box_gray, black, to_cp437('┤'));
+    ctx.set(VIEWPORT_X + 2 + time.len() as i32, 0, box_gray, black, to_cp437('├'));
+    ctx.print_color(VIEWPORT_X + 2, 0, white, black, &time);
+
     // Stats
     let player_entity = ecs.fetch::<Entity>();
     let pools = ecs.read_storage::<Pools>();
@@ -676,8 +699,31 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     draw_attribute("Fitness:", &attrs.fitness, 6, ctx);
     draw_attribute("Intelligence:", &attrs.intelligence, 7, ctx);
 
+    // Encumbrance
+    let capacity = carry_capacity(attrs.might.base + attrs.might.modifiers);
+    let weight_color = if player_pools.total_weight > capacity {
+        RGB::named(RED)
+    } else {
+        white
+    };
+    ctx.print_color(
+        VIEWPORT_X,
+        3,
+        weight_color,
+        black,
+        format!("Weight: {:.1}/{:.0} lbs", player_pools.total_weight, capacity),
+    );
+
+    ctx.print_color(
+        VIEWPORT_X,
+        9,
+        RGB::named(GOLD),
+        black,
+        format!("Gold: {}", player_pools.gold),
+    );
+
     // Equipment
-    let mut y = 9;
+    let mut y = 11;
     let equipped = ecs.read_storage::<Equipped>();
     let name = ecs.read_storage::<Name>();
     for (equipped_by, item_name) in (&equipped, &name).join() {
@@ -702,6 +748,25 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
         }
     }
 
+    y += 1;
+    let blue = RGB::named(CYAN);
+    let known_spells = ecs.read_storage::<KnownSpell>();
+    let mut idx = 1;
+    for (spell, spell_name) in (&known_spells, &name).join() {
+        if spell.owner == *player_entity && idx < 10 {
+            ctx.print_color(VIEWPORT_X, y, yellow, black, format!("^{}", idx));
+            ctx.print_color(
+                VIEWPORT_X + 3,
+                y,
+                blue,
+                black,
+                format!("{} ({})", spell_name.name, spell.mana_cost),
+            );
+            y += 1;
+            idx += 1;
+        }
+    }
+
     // Status
     let hunger = ecs.read_storage::<HungerClock>();
     let hc = hunger.get(*player_entity).unwrap();
@@ -714,15 +779,14 @@ pub fn draw_ui(ecs: &World, ctx: &mut BTerm) {
     if let Some(printables) = hunger_to_print {
         ctx.print_color(VIEWPORT_X, 44, printables.0, printables.1, printables.2);
     }
+    if ecs.read_storage::<Overloaded>().get(*player_entity).is_some() {
+        ctx.print_color(VIEWPORT_X, 43, RGB::named(RED), black, "Overloaded");
+    }
 
     // Logs
     let log = ecs.fetch::<GameLog>();
-    let mut y = VIEWPORT_Y;
-    for s in log.entries.iter().rev() {
-        if y < SCREEN_Y - 1 {
-            ctx.print(2, y, s);
-        }
-        y += 1;
+    for (y, entry) in (VIEWPORT_Y..SCREEN_Y - 1).zip(log.entries(None).rev()) {
+        print_log_entry(ctx, 2, y, entry, (SCREEN_X - 4) as usize);
     }
 
     draw_tooltips(ecs, ctx);
@@ -820,7 +884,7 @@ pub fn unequip_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Op
         y + count as i32 + 1,
         RGB::named(YELLOW),
         RGB::named(BLACK),
-        "ESCAPE to cancel",
+        format!("{} to cancel", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
@@ -835,7 +899,55 @@ pub fn unequip_item_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Op
         j += 1;
     }
 
-    capture_item_options_selection(ctx, equippable, count as i32)
+    capture_item_options_selection(ctx, &gs.ecs.fetch::<KeyBindings>(), equippable, count as i32)
+}
+
+pub fn cast_spell_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let known_spells = gs.ecs.read_storage::<KnownSpell>();
+    let entities = gs.ecs.entities();
+
+    let spells = (&known_spells, &names)
+        .join()
+        .filter(|spell| spell.0.owner == *player_entity);
+    let count = spells.count();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        INVENTORY_X,
+        y - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(WHITE),
+        RGB::named(BLACK),
+    );
+    ctx.print_color(
+        INVENTORY_X + 3,
+        y - 2,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        "Cast Which Spell?",
+    );
+    ctx.print_color(
+        INVENTORY_X + 3,
+        y + count as i32 + 1,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!("{} to cancel", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
+    );
+
+    let mut castable: Vec<Entity> = Vec::new();
+    for (j, (entity, spell, name)) in (&entities, &known_spells, &names)
+        .join()
+        .filter(|spell| spell.1.owner == *player_entity)
+        .enumerate()
+    {
+        print_item_options_menu(&format!("{} ({} mana)", name.name, spell.mana_cost), y + j as i32, j as i32, ctx);
+        castable.push(entity);
+    }
+
+    capture_item_options_selection(ctx, &gs.ecs.fetch::<KeyBindings>(), castable, count as i32)
 }
 
 pub fn print_item_options_menu(name: &String, y: i32, j: i32, ctx: &mut BTerm) {
@@ -866,21 +978,329 @@ pub fn print_item_options_menu(name: &String, y: i32, j: i32, ctx: &mut BTerm) {
 
 pub fn capture_item_options_selection(
     ctx: &mut BTerm,
+    bindings: &KeyBindings,
     options: Vec<Entity>,
     count: i32,
 ) -> (ItemMenuResult, Option<Entity>) {
     match ctx.key {
         None => (ItemMenuResult::NoResponse, None),
-        Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
-            _ => {
-                let selection = letter_to_option(key);
-                if selection > -1 && selection < count {
-                    return (ItemMenuResult::Selected, Some(options[selection as usize]));
+        Some(_) if bindings.pressed(Action::MenuCancel, ctx) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < count {
+                return (ItemMenuResult::Selected, Some(options[selection as usize]));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
+const DIALOGUE_WIDTH: usize = 50;
+
+pub fn show_dialogue(
+    gs: &mut State,
+    ctx: &mut BTerm,
+    speaker: Entity,
+    node: usize,
+) -> (ItemMenuResult, Option<usize>) {
+    let names = gs.ecs.read_storage::<Name>();
+    let dialogues = gs.ecs.read_storage::<Dialogue>();
+    let speaker_name = names.get(speaker).map_or("Someone".to_string(), |n| n.name.clone());
+    let raws = RAWS.lock().unwrap();
+    let current = match dialogues.get(speaker).and_then(|d| dialogue_node(&raws, &d.tree, node)) {
+        None => return (ItemMenuResult::Cancel, None),
+        Some(current) => current,
+    };
+    let choices = available_choices(&gs.ecs, current);
+    let lines = wrap_text(&current.text, DIALOGUE_WIDTH - 4);
+    let height = lines.len() + choices.len() + 4;
+
+    let x = SCREEN_X as usize / 2 - DIALOGUE_WIDTH / 2;
+    let mut y = 25 - (height / 2) as i32;
+    ctx.draw_box(x, y - 2, DIALOGUE_WIDTH, height as i32, RGB::named(WHITE), RGB::named(BLACK));
+    ctx.print_color(x + 3, y - 2, RGB::named(YELLOW), RGB::named(BLACK), &speaker_name);
+    ctx.print_color(
+        x + 3,
+        y + height as i32 - 2,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!("{} to leave", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
+    );
+    for line in lines.iter() {
+        ctx.print_color(x + 2, y, RGB::named(WHEAT), RGB::named(BLACK), line);
+        y += 1;
+    }
+    y += 1;
+    for (j, choice) in choices.iter().enumerate() {
+        ctx.print_color(
+            x + 2,
+            y,
+            RGB::named(WHITE),
+            RGB::named(BLACK),
+            format!("({}) {}", (97 + j as u8) as char, current.choices[*choice].text),
+        );
+        y += 1;
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(_) if gs.ecs.fetch::<KeyBindings>().pressed(Action::MenuCancel, ctx) => (ItemMenuResult::Cancel, None),
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < choices.len() as i32 {
+                return (ItemMenuResult::Selected, Some(choices[selection as usize]));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
+fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if !line.is_empty() && line.len() + word.len() + 1 > width {
+            lines.push(word.to_string());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    lines
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum VendorResult {
+    NoResponse,
+    Cancel,
+    ToggleMode,
+    Buy(usize),
+    Sell(Entity),
+}
+
+pub fn show_vendor(gs: &mut State, ctx: &mut BTerm, vendor: Entity, mode: VendorMode) -> VendorResult {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Name>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let vendors = gs.ecs.read_storage::<Vendor>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let entities = gs.ecs.entities();
+    let standing = vendor_standing(&gs.ecs, vendor);
+    let gold = pools.get(*player_entity).map_or(0, |p| p.gold);
+
+    // Each line is the label, its price and what picking it does
+    let mut options: Vec<(String, i32, VendorResult)> = Vec::new();
+    match mode {
+        VendorMode::Buy => {
+            let raws = RAWS.lock().unwrap();
+            if let Some(vendor) = vendors.get(vendor) {
+                for (i, (key, count)) in vendor.stock.iter().enumerate() {
+                    if *count < 1 {
+                        continue;
+                    }
+                    if let Some((name, value)) = item_name_and_value(&raws, key) {
+                        options.push((
+                            format!("{} (x{})", name, count),
+                            trade_price(value, standing, true),
+                            VendorResult::Buy(i),
+                        ));
+                    }
                 }
-                (ItemMenuResult::NoResponse, None)
             }
-        },
+        }
+        VendorMode::Sell => {
+            for (entity, carried, name) in (&entities, &backpack, &names).join() {
+                if carried.owner == *player_entity {
+                    options.push((
+                        name.name.clone(),
+                        trade_price(item_value(&gs.ecs, entity), standing, false),
+                        VendorResult::Sell(entity),
+                    ));
+                }
+            }
+        }
+    }
+    let count = options.len();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        INVENTORY_X,
+        y - 2,
+        51,
+        (count + 3) as i32,
+        RGB::named(WHITE),
+        RGB::named(BLACK),
+    );
+    let bindings = gs.ecs.fetch::<KeyBindings>();
+    let title = match mode {
+        VendorMode::Buy => format!("Buy Which Item? ({} to sell)", bindings.hint(Action::MenuToggle)),
+        VendorMode::Sell => format!("Sell Which Item? ({} to buy)", bindings.hint(Action::MenuToggle)),
+    };
+    ctx.print_color(INVENTORY_X + 3, y - 2, RGB::named(YELLOW), RGB::named(BLACK), &title);
+    ctx.print_color(
+        INVENTORY_X + 3,
+        y + count as i32 + 1,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!("{} to leave   Gold: {}", bindings.hint(Action::MenuCancel), gold),
+    );
+    for (j, (label, price, _)) in options.iter().enumerate() {
+        let y = y + j as i32;
+        print_item_options_menu(label, y, j as i32, ctx);
+        ctx.print_color(
+            INVENTORY_X + 42,
+            y,
+            RGB::named(GOLD),
+            RGB::named(BLACK),
+            format!("{} gp", price),
+        );
+    }
+
+    match ctx.key {
+        None => VendorResult::NoResponse,
+        Some(_) if bindings.pressed(Action::MenuCancel, ctx) => VendorResult::Cancel,
+        Some(_) if bindings.pressed(Action::MenuToggle, ctx) => VendorResult::ToggleMode,
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return options[selection as usize].2;
+            }
+            VendorResult::NoResponse
+        }
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpSelection {
+    Might,
+    Fitness,
+    Quickness,
+    Intelligence,
+    Skill(Skill),
+}
+
+pub fn level_up_menu(gs: &mut State, ctx: &mut BTerm) -> (ItemMenuResult, Option<LevelUpSelection>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let skills = gs.ecs.read_storage::<Skills>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let attrs = attributes.get(*player_entity).unwrap();
+    let player_skills = skills.get(*player_entity).unwrap();
+    let player_pools = pools.get(*player_entity).unwrap();
+
+    let new_level = player_pools.level + 1;
+    let might = attrs.might.base + attrs.might.modifiers;
+    let fitness = attrs.fitness.base + attrs.fitness.modifiers;
+    let quickness = attrs.quickness.base + attrs.quickness.modifiers;
+    let intelligence = attrs.intelligence.base + attrs.intelligence.modifiers;
+    let skill_level = |skill: Skill| skill_bonus(skill, player_skills);
+
+    let options: Vec<(LevelUpSelection, String)> = vec![
+        (LevelUpSelection::Might, format!("Might: {} -> {}", might, might + 1)),
+        (
+            LevelUpSelection::Fitness,
+            format!(
+                "Fitness: {} -> {} (Max HP {})",
+                fitness,
+                fitness + 1,
+                player_hp_at_level(fitness + 1, new_level)
+            ),
+        ),
+        (LevelUpSelection::Quickness, format!("Quickness: {} -> {}", quickness, quickness + 1)),
+        (
+            LevelUpSelection::Intelligence,
+            format!(
+                "Intelligence: {} -> {} (Max Mana {})",
+                intelligence,
+                intelligence + 1,
+                mana_at_level(intelligence + 1, new_level)
+            ),
+        ),
+        (
+            LevelUpSelection::Skill(Skill::Melee),
+            format!("Melee Skill: {} -> {}", skill_level(Skill::Melee), skill_level(Skill::Melee) + 1),
+        ),
+        (
+            LevelUpSelection::Skill(Skill::Defense),
+            format!("Defense Skill: {} -> {}", skill_level(Skill::Defense), skill_level(Skill::Defense) + 1),
+        ),
+        (
+            LevelUpSelection::Skill(Skill::Magic),
+            format!("Magic Skill: {} -> {}", skill_level(Skill::Magic), skill_level(Skill::Magic) + 1),
+        ),
+        (
+            LevelUpSelection::Skill(Skill::Stealth),
+            format!("Stealth Skill: {} -> {}", skill_level(Skill::Stealth), skill_level(Skill::Stealth) + 1),
+        ),
+        (
+            LevelUpSelection::Skill(Skill::Lockpicking),
+            format!(
+                "Lockpicking Skill: {} -> {}",
+                skill_level(Skill::Lockpicking),
+                skill_level(Skill::Lockpicking) + 1
+            ),
+        ),
+    ];
+    let count = options.len();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        INVENTORY_X,
+        y - 4,
+        45,
+        (count + 5) as i32,
+        RGB::named(WHITE),
+        RGB::named(BLACK),
+    );
+    ctx.print_color(
+        INVENTORY_X + 3,
+        y - 4,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!("Level Up! You have reached level {}", new_level),
+    );
+    ctx.print_color(
+        INVENTORY_X + 2,
+        y - 2,
+        RGB::named(WHITE),
+        RGB::named(BLACK),
+        format!(
+            "Max HP: {} -> {}   Max Mana: {} -> {}",
+            player_pools.hit_points.max,
+            player_hp_at_level(fitness, new_level),
+            player_pools.mana.max,
+            mana_at_level(intelligence, new_level)
+        ),
+    );
+    ctx.print_color(
+        INVENTORY_X + 3,
+        y + count as i32 + 1,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        "Choose an improvement",
+    );
+
+    for (j, option) in options.iter().enumerate() {
+        print_item_options_menu(&option.1, y + j as i32, j as i32, ctx);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            let selection = letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return (ItemMenuResult::Selected, Some(options[selection as usize].0));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
     }
 }
 
@@ -890,10 +1310,24 @@ pub enum GameOverResult {
     QuitToMenu,
 }
 
-pub fn game_over(ctx: &mut BTerm) -> GameOverResult {
-    ctx.print_color_centered(25, RGB::named(YELLOW), RGB::named(BLACK), "RIP You");
+pub fn game_over(ctx: &mut BTerm, stats: &Statistics) -> GameOverResult {
+    ctx.print_color_centered(15, RGB::named(YELLOW), RGB::named(BLACK), "RIP You");
+    let lines = [
+        format!("You made it to depth {}.", stats.deepest_level),
+        format!("You killed {} monsters, earning {} experience.", stats.kills, stats.xp_from_kills),
+        format!("You dealt {} damage and took {}.", stats.damage_dealt, stats.damage_taken),
+        format!("You picked up {} items, {} of them artefacts.", stats.items_picked_up, stats.artefacts_found),
+        format!("You used {} items and cast {} spells.", stats.items_used, stats.spells_cast),
+        format!("You set off {} traps.", stats.traps_triggered),
+        format!("You opened {} doors and picked {} locks.", stats.doors_opened, stats.locks_picked),
+        format!("You spent {} gold and earned {} from selling.", stats.gold_spent, stats.gold_earned),
+        format!("You held {} conversations.", stats.conversations),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        ctx.print_color_centered(17 + i as i32, RGB::named(WHITE), RGB::named(BLACK), line);
+    }
     ctx.print_color_centered(
-        27,
+        18 + lines.len() as i32,
         RGB::named(MAGENTA),
         RGB::named(BLACK),
         "Press any key to return to the main menu",
@@ -904,3 +1338,446 @@ pub fn game_over(ctx: &mut BTerm) -> GameOverResult {
         Some(_) => GameOverResult::QuitToMenu,
     }
 }
+
+// Prints each span of an entry in its own colour, cutting it off at `width` characters
+fn print_log_entry(ctx: &mut BTerm, x: i32, y: i32, entry: &LogEntry, width: usize) {
+    let mut remaining = width;
+    let mut x = x;
+    for span in entry.spans.iter() {
+        if remaining == 0 {
+            break;
+        }
+        let text: String = span.text.chars().take(remaining).collect();
+        ctx.print_color(x, y, span.colour, RGB::named(BLACK), &text);
+        x += text.chars().count() as i32;
+        remaining -= text.chars().count();
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogViewResult {
+    NoResponse,
+    Close,
+    Scroll(usize),
+    Filter(Option<LogCategory>),
+    Export,
+}
+
+/// Full screen scrollback of the message log. `scroll` is how many entries back from the newest the
+/// bottom line is.
+pub fn show_log(gs: &mut State, ctx: &mut BTerm, scroll: usize, filter: Option<LogCategory>) -> LogViewResult {
+    let log = gs.ecs.fetch::<GameLog>();
+    let bindings = gs.ecs.fetch::<KeyBindings>();
+    let entries: Vec<&LogEntry> = log.entries(filter).collect();
+    let height = (SCREEN_Y - 4) as usize;
+    let max_scroll = entries.len().saturating_sub(height);
+    let scroll = usize::min(scroll, max_scroll);
+
+    ctx.draw_box(0, 0, SCREEN_X - 1, SCREEN_Y - 1, RGB::named(WHITE), RGB::named(BLACK));
+    let filter_name = filter.map_or("All", |c| c.name());
+    ctx.print_color(
+        3,
+        0,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!("Message Log ({})", filter_name),
+    );
+    ctx.print_color(
+        3,
+        SCREEN_Y - 1,
+        RGB::named(YELLOW),
+        RGB::named(BLACK),
+        format!(
+            "{}/{}/{}/{} to scroll   {} to filter   {} to export   {} to close",
+            bindings.hint(Action::MenuUp),
+            bindings.hint(Action::MenuDown),
+            bindings.hint(Action::MenuPageUp),
+            bindings.hint(Action::MenuPageDown),
+            bindings.hint(Action::MenuNext),
+            bindings.hint(Action::MenuExport),
+            bindings.hint(Action::MenuCancel)
+        ),
+    );
+
+    let end = entries.len() - scroll;
+    let start = end.saturating_sub(height);
+    for (y, entry) in (2..).zip(entries[start..end].iter()) {
+        let turn = format!("{:>6} ", entry.turn);
+        ctx.print_color(2, y, RGB::named(GREY), RGB::named(BLACK), &turn);
+        print_log_entry(ctx, 2 + turn.len() as i32, y, entry, SCREEN_X as usize - turn.len() - 5);
+    }
+
+    let page = height.saturating_sub(1);
+    match bindings.action(BindingContext::Menu, ctx) {
+        None => LogViewResult::NoResponse,
+        Some(action) => match action {
+            Action::MenuCancel => LogViewResult::Close,
+            Action::MenuUp => LogViewResult::Scroll(usize::min(scroll + 1, max_scroll)),
+            Action::MenuDown => LogViewResult::Scroll(scroll.saturating_sub(1)),
+            Action::MenuPageUp => LogViewResult::Scroll(usize::min(scroll + page, max_scroll)),
+            Action::MenuPageDown => LogViewResult::Scroll(scroll.saturating_sub(page)),
+            Action::MenuNext => {
+                let next = match filter {
+                    None => Some(LogCategory::ALL[0]),
+                    Some(current) => {
+                        let i = LogCategory::ALL.iter().position(|c| *c == current).unwrap_or(0);
+                        LogCategory::ALL.get(i + 1).copied()
+                    }
+                };
+                LogViewResult::Filter(next)
+            }
+            Action::MenuExport => LogViewResult::Export,
+            _ => LogViewResult::NoResponse,
+        },
+    }
+}
+
+const SHEET_SLOTS: [EquipmentSlot; 7] = [
+    EquipmentSlot::Melee,
+    EquipmentSlot::Shield,
+    EquipmentSlot::Head,
+    EquipmentSlot::Torso,
+    EquipmentSlot::Legs,
+    EquipmentSlot::Feet,
+    EquipmentSlot::Hands,
+];
+
+// Prints a total and, beneath it, each non-zero part that went into it
+fn print_breakdown(ctx: &mut BTerm, x: i32, y: i32, title: &str, sources: &[(&'static str, i32)]) -> i32 {
+    let total: i32 = sources.iter().map(|(_, bonus)| bonus).sum();
+    ctx.print_color(x, y, RGB::named(YELLOW), RGB::named(BLACK), format!("{}: {}", title, total));
+    let mut y = y + 1;
+    for (source, bonus) in sources.iter().filter(|(_, bonus)| *bonus != 0) {
+        let colour = if *bonus < 0 { RGB::named(RED) } else { RGB::named(GREY) };
+        ctx.print_color(x + 2, y, colour, RGB::named(BLACK), format!("{:<14}{:+}", source, bonus));
+        y += 1;
+    }
+    y
+}
+
+pub fn show_character_sheet(gs: &mut State, ctx: &mut BTerm) -> ItemMenuResult {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let skills = gs.ecs.read_storage::<Skills>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let names = gs.ecs.read_storage::<Name>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let wearables = gs.ecs.read_storage::<Wearable>();
+    let melee_weapons = gs.ecs.read_storage::<MeleeWeapon>();
+    let ranged_weapons = gs.ecs.read_storage::<RangedWeapon>();
+    let hunger = gs.ecs.read_storage::<HungerClock>();
+    let attrs = attributes.get(*player_entity).unwrap();
+    let player_skills = skills.get(*player_entity).unwrap();
+    let player_pools = pools.get(*player_entity).unwrap();
+    let hunger_state = hunger.get(*player_entity).map_or(HungerState::Normal, |hc| hc.state);
+    let overloaded = gs.ecs.read_storage::<Overloaded>().get(*player_entity).is_some();
+
+    let white = RGB::named(WHITE);
+    let grey = RGB::named(GREY);
+    let yellow = RGB::named(YELLOW);
+    let black = RGB::named(BLACK);
+
+    ctx.draw_box(0, 0, SCREEN_X - 1, SCREEN_Y - 1, white, black);
+    ctx.print_color(3, 0, yellow, black, format!("Character Sheet - Level {}", player_pools.level));
+    ctx.print_color(
+        3,
+        SCREEN_Y - 1,
+        yellow,
+        black,
+        format!("{} to close", gs.ecs.fetch::<KeyBindings>().hint(Action::MenuCancel)),
+    );
+
+    // Pools
+    let left = 3;
+    let mut y = 2;
+    ctx.print_color(left, y, yellow, black, "Pools");
+    let next_level = xp_for_next_level(player_pools.level);
+    let pool_lines = [
+        format!("Hit Points: {}/{}", player_pools.hit_points.current, player_pools.hit_points.max),
+        format!("Mana:       {}/{}", player_pools.mana.current, player_pools.mana.max),
+        format!(
+            "Experience: {}/{} ({} to level {})",
+            player_pools.xp,
+            next_level,
+            i32::max(0, next_level - player_pools.xp),
+            player_pools.level + 1
+        ),
+        format!(
+            "Carrying:   {:.1}/{:.0} lbs",
+            player_pools.total_weight,
+            carry_capacity(attrs.might.base + attrs.might.modifiers)
+        ),
+    ];
+    for line in pool_lines.iter() {
+        y += 1;
+        ctx.print_color(left + 2, y, white, black, line);
+    }
+
+    // Attributes
+    y += 2;
+    ctx.print_color(left, y, yellow, black, "Attribute      Base  Mod  Total  Bonus");
+    let attribute_rows = [
+        ("Might", &attrs.might),
+        ("Fitness", &attrs.fitness),
+        ("Quickness", &attrs.quickness),
+        ("Intelligence", &attrs.intelligence),
+    ];
+    for (name, attribute) in attribute_rows.iter() {
+        y += 1;
+        let colour = if attribute.modifiers < 0 {
+            RGB::named(RED)
+        } else if attribute.modifiers > 0 {
+            RGB::named(GREEN)
+        } else {
+            white
+        };
+        ctx.print_color(left + 2, y, grey, black, name);
+        ctx.print_color(
+            left + 15,
+            y,
+            colour,
+            black,
+            format!(
+                "{:>4} {:>+4} {:>6} {:>+6}",
+                attribute.base,
+                attribute.modifiers,
+                attribute.base + attribute.modifiers,
+                attribute.bonus
+            ),
+        );
+    }
+
+    // Skills
+    y += 2;
+    ctx.print_color(left, y, yellow, black, "Skills");
+    let skill_rows = [
+        ("Melee", Skill::Melee),
+        ("Defense", Skill::Defense),
+        ("Magic", Skill::Magic),
+        ("Stealth", Skill::Stealth),
+        ("Lockpicking", Skill::Lockpicking),
+    ];
+    for (name, skill) in skill_rows.iter() {
+        y += 1;
+        ctx.print_color(left + 2, y, grey, black, name);
+        ctx.print_color(left + 17, y, white, black, format!("{:+}", skill_bonus(*skill, player_skills)));
+    }
+
+    // Status
+    y += 2;
+    ctx.print_color(left, y, yellow, black, "Status");
+    let mut effects: Vec<(RGB, String)> = Vec::new();
+    match hunger_state {
+        HungerState::WellFed => effects.push((RGB::named(GREEN), "Well Fed (+1 to hit, faster regeneration)".to_string())),
+        HungerState::Normal => {}
+        HungerState::Hungry => effects.push((RGB::named(ORANGE), "Hungry (slower regeneration)".to_string())),
+        HungerState::Starving => effects.push((RGB::named(RED), "Starving (no regeneration, taking damage)".to_string())),
+    }
+    if overloaded {
+        effects.push((
+            RGB::named(RED),
+            format!("Overloaded (-{} to hit and armour, slower)", OVERLOADED_COMBAT_PENALTY),
+        ));
+    }
+    if let Some(confusion) = gs.ecs.read_storage::<Confusion>().get(*player_entity) {
+        effects.push((RGB::named(MAGENTA), format!("Confused ({} turns)", confusion.turns)));
+    }
+    match hp_regen_interval(attrs.fitness.bonus, hunger_state) {
+        Some(interval) => effects.push((grey, format!("Regaining 1 HP every {} turns", interval))),
+        None => effects.push((grey, "Not regaining HP".to_string())),
+    }
+    let magic = skill_bonus(Skill::Magic, player_skills);
+    if let Some(interval) = mana_regen_interval(attrs.intelligence.bonus, magic, hunger_state) {
+        effects.push((grey, format!("Regaining 1 Mana every {} turns", interval)));
+    }
+    for (colour, effect) in effects.iter() {
+        y += 1;
+        ctx.print_color(left + 2, y, *colour, black, effect);
+    }
+
+    // Equipment
+    let right = SCREEN_X / 2 + 2;
+    let mut y = 2;
+    ctx.print_color(right, y, yellow, black, "Equipment");
+    let mut weapon = unarmed_weapon();
+    let mut armor_item_bonus = 0.0;
+    for slot in SHEET_SLOTS.iter() {
+        y += 1;
+        ctx.print_color(right + 2, y, grey, black, format!("{:?}", slot));
+        let item = (&gs.ecs.entities(), &equipped, &names)
+            .join()
+            .find(|(_, e, _)| e.owner == *player_entity && e.slot == *slot);
+        match item {
+            None => ctx.print_color(right + 10, y, grey, black, "-"),
+            Some((item, _, name)) => {
+                let mut description = name.name.clone();
+                if let Some(wearable) = wearables.get(item) {
+                    armor_item_bonus += wearable.armor_class;
+                    description = format!("{} (AC {:+})", description, wearable.armor_class);
+                }
+                if let Some(melee) = melee_weapons.get(item) {
+                    if *slot == EquipmentSlot::Melee && ranged_weapons.get(item).is_none() {
+                        weapon = melee.clone();
+                    }
+                    description = format!(
+                        "{} ({}d{}{:+})",
+                        description, melee.damage_n_dice, melee.damage_die_type, melee.damage_bonus
+                    );
+                }
+                ctx.print_color(right + 10, y, white, black, &description);
+            }
+        }
+    }
+
+    // Combat figures, worked out the same way the combat systems do
+    y += 2;
+    let armour = armor_class_sources(None, attrs, player_skills, armor_item_bonus, overloaded);
+    y = print_breakdown(ctx, right, y, "Armour Class", &armour);
+    y += 1;
+    let well_fed = hunger_state == HungerState::WellFed;
+    let hit = hit_bonus_sources(attrs, player_skills, &weapon, well_fed, overloaded);
+    print_breakdown(ctx, right, y, "Melee Hit Bonus", &hit);
+
+    if gs.ecs.fetch::<KeyBindings>().pressed(Action::MenuCancel, ctx) {
+        ItemMenuResult::Cancel
+    } else {
+        ItemMenuResult::NoResponse
+    }
+}
+
+fn health_estimate(pools: &Pools) -> (RGB, &'static str) {
+    let health = pools.hit_points.current as f32 / i32::max(1, pools.hit_points.max) as f32;
+    if health >= 1.0 {
+        (RGB::named(GREEN), "Unhurt")
+    } else if health > 0.75 {
+        (RGB::named(GREEN), "Lightly wounded")
+    } else if health > 0.5 {
+        (RGB::named(YELLOW), "Wounded")
+    } else if health > 0.25 {
+        (RGB::named(ORANGE), "Badly wounded")
+    } else {
+        (RGB::named(RED), "Nearly dead")
+    }
+}
+
+/// Draws the look cursor at map position (x, y) and a panel describing what is there
+pub fn draw_look(ecs: &World, ctx: &mut BTerm, x: i32, y: i32) {
+    let (min_x, _max_x, min_y, _max_y) = get_screen_bounds(ecs, ctx);
+    let map = ecs.fetch::<Map>();
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let examinable = ecs.read_storage::<Examinable>();
+    let attrs = ecs.read_storage::<Attributes>();
+    let pools = ecs.read_storage::<Pools>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let asleep = ecs.read_storage::<Asleep>();
+    let entities = ecs.entities();
+
+    let white = RGB::named(WHITE);
+    let grey = RGB::named(GREY);
+    let black = RGB::named(BLACK);
+    let width = 44;
+
+    ctx.set_bg(x - min_x, y - min_y, RGB::named(MAGENTA));
+
+    let mut lines: Vec<(RGB, String)> = Vec::new();
+    if !map.revealed_tiles[x as usize][y as usize] {
+        lines.push((grey, "You don't know what is there.".to_string()));
+    } else {
+        let tile = map.tiles[x as usize][y as usize];
+        lines.push((grey, tile_name(tile).to_string()));
+        if !map.visible_tiles[x as usize][y as usize] {
+            lines.push((grey, "You can't see this spot right now.".to_string()));
+        } else {
+            for (entity, name, pos, _h) in (&entities, &names, &positions, !&hidden).join() {
+                if pos.x != x || pos.y != y {
+                    continue;
+                }
+                lines.push((black, String::new()));
+                let title = if entity == *player_entity { format!("{} (you)", name.name) } else { name.name.clone() };
+                lines.push((RGB::named(YELLOW), title));
+                if let Some(examinable) = examinable.get(entity) {
+                    for line in wrap_text(&examinable.description, width as usize - 4) {
+                        lines.push((white, line));
+                    }
+                }
+                if let Some(stats) = pools.get(entity) {
+                    let (colour, health) = health_estimate(stats);
+                    lines.push((colour, format!("{}. Level {}.", health, stats.level)));
+                }
+                if let Some(att) = attrs.get(entity) {
+                    lines.push((grey, attribute_summary(att)));
+                }
+                if asleep.get(entity).is_some() {
+                    lines.push((RGB::named(CYAN), "Asleep.".to_string()));
+                }
+                let gear: Vec<String> = (&equipped, &names)
+                    .join()
+                    .filter(|(e, _)| e.owner == entity)
+                    .map(|(_, n)| n.name.clone())
+                    .collect();
+                if !gear.is_empty() {
+                    for line in wrap_text(&format!("Equipped: {}.", gear.join(", ")), width as usize - 4) {
+                        lines.push((grey, line));
+                    }
+                }
+            }
+        }
+    }
+
+    let box_x = if x - min_x < VIEWPORT_X / 2 { VIEWPORT_X - width - 2 } else { 1 };
+    let height = lines.len() as i32 + 1;
+    ctx.draw_box(box_x, 1, width, height, white, black);
+    ctx.print_color(box_x + 2, 1, RGB::named(YELLOW), black, "Look");
+    let bindings = ecs.fetch::<KeyBindings>();
+    ctx.print_color(
+        box_x + 2,
+        1 + height,
+        RGB::named(YELLOW),
+        black,
+        format!("{} next thing  {} done", bindings.hint(Action::MenuNext), bindings.hint(Action::MenuCancel)),
+    );
+    for (i, (colour, line)) in lines.iter().enumerate() {
+        ctx.print_color(box_x + 2, 2 + i as i32, *colour, black, line);
+    }
+}
+
+/// Lists every bound command, built from the key bindings so it always matches them
+pub fn show_help(gs: &mut State, ctx: &mut BTerm) -> ItemMenuResult {
+    let bindings = gs.ecs.fetch::<KeyBindings>();
+    let yellow = RGB::named(YELLOW);
+    let black = RGB::named(BLACK);
+
+    ctx.draw_box(0, 0, SCREEN_X - 1, SCREEN_Y - 1, RGB::named(WHITE), black);
+    ctx.print_color(3, 0, yellow, black, "Commands");
+    ctx.print_color(
+        3,
+        SCREEN_Y - 1,
+        yellow,
+        black,
+        format!("{} to close", bindings.hint(Action::MenuCancel)),
+    );
+
+    let sections = [(BindingContext::Game, "In the dungeon"), (BindingContext::Menu, "In menus")];
+    let mut y = 2;
+    for (context, title) in sections.iter() {
+        ctx.print_color(3, y, yellow, black, title);
+        y += 1;
+        for action in Action::ALL.iter().filter(|a| a.context() == *context) {
+            ctx.print_color(5, y, RGB::named(GREY), black, action.description());
+            ctx.print_color(37, y, RGB::named(WHITE), black, bindings.describe(*action));
+            y += 1;
+        }
+        y += 1;
+    }
+    ctx.print_color(5, y, RGB::named(GREY), black, "Choose from a list");
+    ctx.print_color(37, y, RGB::named(WHITE), black, "The letter next to it");
+
+    if bindings.pressed(Action::MenuCancel, ctx) {
+        ItemMenuResult::Cancel
+    } else {
+        ItemMenuResult::NoResponse
+    }
+}
@@ -2,8 +2,8 @@ use std::cmp::min;
 
 use specs::prelude::*;
 
-use crate::components::{HungerClock, HungerState, SufferDamage};
-use crate::gamelog::GameLog;
+use crate::components::{HungerClock, HungerState, MyTurn, SufferDamage};
+use crate::gamelog::{GameLog, LogCategory};
 use crate::RunState;
 
 pub struct HungerSystem {}
@@ -46,7 +46,8 @@ impl <'a> System<'a> for HungerSystem {
         ReadExpect<'a, Entity>,
         ReadExpect<'a, RunState>,
         WriteStorage<'a, SufferDamage>,
-        WriteExpect<'a, GameLog>
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, MyTurn>
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -56,14 +57,15 @@ impl <'a> System<'a> for HungerSystem {
             player_entity,
             runstate,
             mut inflict_damage,
-            mut gamelog
+            mut gamelog,
+            turns
         ) = data;
 
         for (entity, clock) in (&entities, &mut hunger_clock).join() {
-            let proceed = match *runstate {
-                RunState::PlayerTurn if entity == *player_entity => true,
-                RunState::MonsterTurn if entity != *player_entity => true,
-                _ => false
+            let proceed = if entity == *player_entity {
+                *runstate == RunState::PlayerTurn
+            } else {
+                turns.get(entity).is_some()
             };
 
             if proceed {
@@ -74,29 +76,29 @@ impl <'a> System<'a> for HungerSystem {
                             clock.state = HungerState::Normal;
                             clock.hunger_points = 200;
                             if entity == *player_entity {
-                                gamelog.entries.push("You are no longer well fed".to_string());
+                                gamelog.add(LogCategory::System, "You are no longer well fed");
                             }
                         }
                         HungerState::Normal => {
                             clock.state = HungerState::Hungry;
                             clock.hunger_points = 200;
                             if entity == *player_entity {
-                                gamelog.entries.push("You are getting hungry".to_string());
+                                gamelog.add(LogCategory::System, "You are getting hungry");
                             }
                         }
                         HungerState::Hungry => {
                             clock.state = HungerState::Starving;
                             clock.hunger_points = 200;
                             if entity == *player_entity {
-                                gamelog.entries.push("You are starving".to_string());
+                                gamelog.add(LogCategory::System, "You are starving");
                             }
                         }
                         HungerState::Starving => {
                             clock.hunger_points = 200;
                             if entity == *player_entity {
-                                gamelog.entries.push("You are dying of starvation. You take 1 hp of damage".to_string());
+                                gamelog.add(LogCategory::System, "You are dying of starvation. You take 1 hp of damage");
                             }
-                            SufferDamage::new_damage(&mut inflict_damage, entity, 1);
+                            SufferDamage::new_damage(&mut inflict_damage, entity, 1, false, None);
                         }
                     }
                 }
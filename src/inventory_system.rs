@@ -2,11 +2,15 @@ use bracket_lib::color::{BLACK, GREEN, MAGENTA, ORANGE, RED, RGB};
 use bracket_lib::prelude::{field_of_view, to_cp437};
 use specs::prelude::*;
 
-use crate::components::{AreaOfEffect, Artefact, Confusion, Consumable, Equippable, Equipped, HungerClock, InBackpack, InflictsDamage, MagicMapper, Name, Pools, Position, ProvidesFood, ProvidesHealing, SufferDamage, WantsToDropItem, WantsToPickUpItem, WantsToUnequipItem, WantsToUseItem};
-use crate::gamelog::GameLog;
+use crate::components::{AreaOfEffect, Artefact, Attributes, Confusion, Consumable, Energy, EquipmentChanged, EquipmentSlot, Equippable, Equipped, HungerClock, InBackpack, InflictsDamage, KnownSpell, MagicMapper, Name, Pools, Position, ProvidesFood, ProvidesHealing, Skill, Skills, SufferDamage, TeachesSpell, TwoHanded, WantsToDropItem, WantsToPickUpItem, WantsToUnequipItem, WantsToUseItem};
+use crate::energy_system::{DROP_COST, PICK_UP_COST, UNEQUIP_COST, USE_ITEM_COST};
+use crate::events::{GameEvent, GameEvents};
+use crate::gamelog::{GameLog, LogBuilder, LogCategory};
+use crate::gamesystem::{skill_bonus, spell_power_bonus};
 use crate::hunger_system::HungerSystem;
 use crate::map::Map;
 use crate::particle_system::ParticleBuilder;
+use crate::spell_system::learn_spell;
 use crate::RunState;
 
 pub struct ItemCollectionSystem {}
@@ -18,18 +22,27 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, WantsToPickUpItem>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
-        WriteStorage<'a, InBackpack>
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, EquipmentChanged>,
+        WriteExpect<'a, GameEvents>
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) = data;
+        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack, mut energy, mut dirty, mut events) = data;
 
         for pickup in wants_pickup.join() {
+            Energy::spend(&mut energy, pickup.collected_by, PICK_UP_COST);
+            dirty.insert(pickup.collected_by, EquipmentChanged {}).expect("Unable to insert");
             positions.remove(pickup.item);
+            events.publish(GameEvent::ItemPickedUp { item: pickup.item, by: pickup.collected_by });
             backpack.insert(pickup.item, InBackpack { owner: pickup.collected_by})
                 .expect("Failed to insert item in backpack");
             if pickup.collected_by == *player_entity {
-                gamelog.entries.push(format!("You picked up the {}", names.get(pickup.item).unwrap().name));
+                LogBuilder::new(LogCategory::Loot)
+                    .append("You picked up the ")
+                    .item(&names.get(pickup.item).unwrap().name)
+                    .log(&mut gamelog);
             }
 
         }
@@ -64,6 +77,17 @@ impl<'a> System<'a> for ItemUseSystem {
         WriteStorage<'a, HungerClock>,
         ReadStorage<'a, MagicMapper>,
         WriteExpect<'a, RunState>,
+        WriteStorage<'a, Energy>,
+        (
+            ReadStorage<'a, KnownSpell>,
+            ReadStorage<'a, TeachesSpell>,
+            ReadStorage<'a, Attributes>,
+            ReadStorage<'a, Skills>,
+            Read<'a, LazyUpdate>,
+            WriteStorage<'a, EquipmentChanged>,
+            ReadStorage<'a, TwoHanded>,
+            WriteExpect<'a, GameEvents>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -90,13 +114,28 @@ impl<'a> System<'a> for ItemUseSystem {
             provides_food,
             mut hunger_clock,
             magic_mapper,
-            mut runsatate
+            mut runsatate,
+            mut energy,
+            (known_spells, teaches_spell, attributes, skills, lazy, mut dirty, two_handed, mut events)
         ) = data;
 
         for (entity, use_item) in (&entities, &wants_use_item).join() {
+            Energy::spend(&mut energy, entity, USE_ITEM_COST);
+            dirty.insert(entity, EquipmentChanged {}).expect("Unable to insert");
+            events.publish(GameEvent::ItemUsed { item: use_item.item, by: entity });
+            let is_spell = known_spells.get(use_item.item).is_some();
+            let verb = if is_spell { "cast" } else { "use" };
+            let spell_bonus = if is_spell {
+                spell_power_bonus(
+                    skills.get(entity).map_or(0, |s| skill_bonus(Skill::Magic, s)),
+                    attributes.get(entity).map_or(0, |a| a.intelligence.bonus)
+                )
+            } else {
+                0
+            };
             let mut targets: Vec<Entity> = Vec::new();
             match use_item.target {
-                None => { targets.push(*player_entity); }
+                None => { targets.push(entity); }
                 Some(target) => {
                     let area_effect = area_of_effect.get(use_item.item);
                     match area_effect {
@@ -129,10 +168,17 @@ impl<'a> System<'a> for ItemUseSystem {
                     let target = targets[0];
 
                     let mut to_unequip: Vec<Entity> = Vec::new();
+                    let is_two_handed = two_handed.get(use_item.item).is_some();
                     for (item_entity, already_equipped, name) in (&entities, &equipped, &names).join() {
-                        if already_equipped.owner == target && already_equipped.slot == target_slot {
+                        if already_equipped.owner != target {
+                            continue;
+                        }
+                        // A two-handed weapon and a shield can't be held at the same time
+                        let hands_full = (is_two_handed && already_equipped.slot == EquipmentSlot::Shield) ||
+                            (target_slot == EquipmentSlot::Shield && two_handed.get(item_entity).is_some());
+                        if already_equipped.slot == target_slot || hands_full {
                             to_unequip.push(item_entity);
-                            gamelog.entries.push(format!("You unequip {}", name.name));
+                            gamelog.add(LogCategory::Loot, format!("You unequip {}", name.name));
                         }
                     }
                     for item in to_unequip.iter() {
@@ -143,7 +189,7 @@ impl<'a> System<'a> for ItemUseSystem {
                     equipped.insert(use_item.item, Equipped { owner: target, slot: target_slot}).expect("Unable to equip item");
                     in_backpack.remove(use_item.item);
                     if target == *player_entity {
-                        gamelog.entries.push(format!("You equip {}", names.get(use_item.item).unwrap().name));
+                        gamelog.add(LogCategory::Loot, format!("You equip {}", names.get(use_item.item).unwrap().name));
                     }
                 }
             }
@@ -157,7 +203,7 @@ impl<'a> System<'a> for ItemUseSystem {
                     );
                     hc.hunger_points = updated_state.1;
                     hc.state = updated_state.0;
-                    gamelog.entries.push(format!(
+                    gamelog.add(LogCategory::Loot, format!(
                         "You eat the {}. It fills you up.", names.get(use_item.item).unwrap().name
                     ))
                 }
@@ -170,9 +216,14 @@ impl<'a> System<'a> for ItemUseSystem {
                     for target in targets.iter() {
                         let p = pools.get_mut(*target);
                         if let Some(p) = p {
-                            p.hit_points.current = i32::min(p.hit_points.max, p.hit_points.current + healer.heal_amount);
+                            let heal_amount = healer.heal_amount + spell_bonus;
+                            p.hit_points.current = i32::min(p.hit_points.max, p.hit_points.current + heal_amount);
                             if entity == *player_entity {
-                                gamelog.entries.push(format!("You drink the {}, and it heals {}hp", names.get(use_item.item).unwrap().name, healer.heal_amount));
+                                if is_spell {
+                                    gamelog.add(LogCategory::Loot, format!("You cast {}, and it heals {}hp", names.get(use_item.item).unwrap().name, heal_amount));
+                                } else {
+                                    gamelog.add(LogCategory::Loot, format!("You drink the {}, and it heals {}hp", names.get(use_item.item).unwrap().name, heal_amount));
+                                }
                             }
                             let pos = positions.get(*target);
                             if let Some(pos) = pos {
@@ -189,7 +240,7 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => {}
                 Some(art) => {
                     if entity == *player_entity {
-                        gamelog.entries.push(format!("This artefact is named {}, and it is worth {} gold", art.name, art.value));
+                        gamelog.add(LogCategory::Loot, format!("This artefact is named {}, and it is worth {} gold", art.name, art.value));
                     }
                 }
             }
@@ -198,24 +249,37 @@ impl<'a> System<'a> for ItemUseSystem {
             match item_damages {
                 None => {}
                 Some(damage) => {
+                    let damage_amount = damage.damage + spell_bonus;
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage_amount, entity == *player_entity, Some(entity));
                         if entity == *player_entity {
                             let mob_name = names.get(*mob).unwrap();
                             let item_name = names.get(use_item.item).unwrap();
-                            gamelog.entries.push(
-                                format!("You use {} on {}, inflicting {} damage",
-                                        item_name.name,
-                                        mob_name.name,
-                                        damage.damage
-                                )
-                            );
+                            LogBuilder::new(LogCategory::Combat)
+                                .append(format!("You {} ", verb))
+                                .item(&item_name.name)
+                                .append(" on ")
+                                .name(&mob_name.name)
+                                .append(", inflicting ")
+                                .damage(damage_amount)
+                                .append(" damage")
+                                .log(&mut gamelog);
                             let pos = positions.get(*mob);
                             if let Some(pos) = pos {
                                 particle_builder.request(
                                     pos.x, pos.y, RGB::named(RED), RGB::named(BLACK), to_cp437('!'), 200.0
                                 );
                             }
+                        } else if *mob == *player_entity {
+                            LogBuilder::new(LogCategory::Combat)
+                                .append("The ")
+                                .name(&names.get(entity).unwrap().name)
+                                .append(format!(" {}s ", verb))
+                                .item(&names.get(use_item.item).unwrap().name)
+                                .append(" on you, inflicting ")
+                                .damage(damage_amount)
+                                .append(" damage")
+                                .log(&mut gamelog);
                         }
                     }
                 }
@@ -231,26 +295,39 @@ impl<'a> System<'a> for ItemUseSystem {
                             if entity == *player_entity {
                                 let mob_name = names.get(*mob).unwrap();
                                 let item_name = names.get(use_item.item).unwrap();
-                                gamelog.entries.push(
-                                    format!("You use {} on {}, confusing them",
-                                            item_name.name,
-                                            mob_name.name,
-                                    )
-                                );
+                                LogBuilder::new(LogCategory::Combat)
+                                    .append(format!("You {} ", verb))
+                                    .item(&item_name.name)
+                                    .append(" on ")
+                                    .name(&mob_name.name)
+                                    .append(", confusing them")
+                                    .log(&mut gamelog);
                                 let pos = positions.get(*mob);
                                 if let Some(pos) = pos {
                                     particle_builder.request(
                                         pos.x, pos.y, RGB::named(MAGENTA), RGB::named(BLACK), to_cp437('?'), 200.0
                                     );
                                 }
+                            } else if *mob == *player_entity {
+                                LogBuilder::new(LogCategory::Combat)
+                                    .append("The ")
+                                    .name(&names.get(entity).unwrap().name)
+                                    .append(format!(" {}s ", verb))
+                                    .item(&names.get(use_item.item).unwrap().name)
+                                    .append(" on you. You feel confused!")
+                                    .log(&mut gamelog);
                             }
                         }
                     }
                 }
             }
+            if let Some(teaches) = teaches_spell.get(use_item.item) {
+                let spell = teaches.spell.clone();
+                lazy.exec_mut(move |world| learn_spell(world, entity, &spell));
+            }
             let is_mapper = magic_mapper.get(use_item.item);
             if let Some(_m) = is_mapper {
-                gamelog.entries.push("You use the scroll, which reveals the map to you".to_string());
+                gamelog.add(LogCategory::Loot, "You use the scroll, which reveals the map to you");
                 *runsatate = RunState::MagicMapReveal{ row: 0 };
             };
             for mob in add_confusion.iter() {
@@ -279,13 +356,17 @@ impl<'a> System<'a> for ItemDropSystem {
         WriteStorage<'a, WantsToDropItem>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
-        WriteStorage<'a, InBackpack>
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, EquipmentChanged>
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, entities, mut wants_drop, names, mut positions, mut backpack) = data;
+        let (player_entity, mut gamelog, entities, mut wants_drop, names, mut positions, mut backpack, mut energy, mut dirty) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
+            Energy::spend(&mut energy, entity, DROP_COST);
+            dirty.insert(entity, EquipmentChanged {}).expect("Unable to insert");
             let mut dropper_pos: Position = Position{x: 0, y: 0};
             {
                 let dropped_pos = positions.get(entity).unwrap();
@@ -298,7 +379,10 @@ impl<'a> System<'a> for ItemDropSystem {
             backpack.remove(to_drop.item);
 
             if entity == *player_entity {
-                gamelog.entries.push(format!("You drop the {}", names.get(to_drop.item).unwrap().name));
+                LogBuilder::new(LogCategory::Loot)
+                    .append("You drop the ")
+                    .item(&names.get(to_drop.item).unwrap().name)
+                    .log(&mut gamelog);
             }
         }
         wants_drop.clear();
@@ -312,13 +396,17 @@ impl<'a> System<'a> for ItemUnequippingSystem {
         Entities<'a>,
         WriteStorage<'a, WantsToUnequipItem>,
         WriteStorage<'a, Equipped>,
-        WriteStorage<'a, InBackpack>
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Energy>,
+        WriteStorage<'a, EquipmentChanged>
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut want_to_unequip, mut equipped, mut backpack) = data;
+        let (entities, mut want_to_unequip, mut equipped, mut backpack, mut energy, mut dirty) = data;
 
         for (entity, to_unequip) in (&entities, &want_to_unequip).join() {
+            Energy::spend(&mut energy, entity, UNEQUIP_COST);
+            dirty.insert(entity, EquipmentChanged {}).expect("Unable to insert");
             equipped.remove(to_unequip.item);
             backpack.insert(to_unequip.item, InBackpack{ owner: entity})
                 .expect("Unable to insert item into backpack");
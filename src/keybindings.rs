@@ -0,0 +1,674 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bracket_lib::prelude::{BTerm, VirtualKeyCode};
+
+/// Settings entries named `key_<action>` replace that action's default keys, e.g.
+/// `key_move_west = "Left, H, Numpad4"` or `key_help = "F1, Shift+Slash"`
+pub const SETTINGS_PREFIX: &str = "key_";
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum BindingContext {
+    Game,
+    Menu,
+}
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum Action {
+    MoveWest,
+    MoveEast,
+    MoveNorth,
+    MoveSouth,
+    MoveNorthWest,
+    MoveNorthEast,
+    MoveSouthWest,
+    MoveSouthEast,
+    Wait,
+    PickUp,
+    Inventory,
+    Drop,
+    Unequip,
+    CastSpell,
+    Fire,
+    OpenDoor,
+    CloseDoor,
+    Descend,
+    MessageLog,
+    CharacterSheet,
+    Look,
+    AutoExplore,
+    TravelToStairs,
+    Help,
+    SaveAndQuit,
+    MenuUp,
+    MenuDown,
+    MenuPageUp,
+    MenuPageDown,
+    MenuSelect,
+    MenuCancel,
+    MenuNext,
+    MenuToggle,
+    MenuExport,
+    UseConsumable(u8),
+    QuickCast(u8),
+}
+
+impl Action {
+    pub const ALL: [Action; 52] = [
+        Action::MoveWest,
+        Action::MoveEast,
+        Action::MoveNorth,
+        Action::MoveSouth,
+        Action::MoveNorthWest,
+        Action::MoveNorthEast,
+        Action::MoveSouthWest,
+        Action::MoveSouthEast,
+        Action::Wait,
+        Action::PickUp,
+        Action::Inventory,
+        Action::Drop,
+        Action::Unequip,
+        Action::CastSpell,
+        Action::Fire,
+        Action::OpenDoor,
+        Action::CloseDoor,
+        Action::Descend,
+        Action::MessageLog,
+        Action::CharacterSheet,
+        Action::Look,
+        Action::AutoExplore,
+        Action::TravelToStairs,
+        Action::Help,
+        Action::SaveAndQuit,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuPageUp,
+        Action::MenuPageDown,
+        Action::MenuSelect,
+        Action::MenuCancel,
+        Action::MenuNext,
+        Action::MenuToggle,
+        Action::MenuExport,
+        Action::UseConsumable(0),
+        Action::UseConsumable(1),
+        Action::UseConsumable(2),
+        Action::UseConsumable(3),
+        Action::UseConsumable(4),
+        Action::UseConsumable(5),
+        Action::UseConsumable(6),
+        Action::UseConsumable(7),
+        Action::UseConsumable(8),
+        Action::QuickCast(0),
+        Action::QuickCast(1),
+        Action::QuickCast(2),
+        Action::QuickCast(3),
+        Action::QuickCast(4),
+        Action::QuickCast(5),
+        Action::QuickCast(6),
+        Action::QuickCast(7),
+        Action::QuickCast(8),
+    ];
+
+    pub fn context(&self) -> BindingContext {
+        match self {
+            Action::MenuUp
+            | Action::MenuDown
+            | Action::MenuPageUp
+            | Action::MenuPageDown
+            | Action::MenuSelect
+            | Action::MenuCancel
+            | Action::MenuNext
+            | Action::MenuToggle
+            | Action::MenuExport => BindingContext::Menu,
+            _ => BindingContext::Game,
+        }
+    }
+
+    /// The name used for the action in the settings file
+    pub fn setting_name(&self) -> &'static str {
+        match self {
+            Action::MoveWest => "move_west",
+            Action::MoveEast => "move_east",
+            Action::MoveNorth => "move_north",
+            Action::MoveSouth => "move_south",
+            Action::MoveNorthWest => "move_north_west",
+            Action::MoveNorthEast => "move_north_east",
+            Action::MoveSouthWest => "move_south_west",
+            Action::MoveSouthEast => "move_south_east",
+            Action::Wait => "wait",
+            Action::PickUp => "pick_up",
+            Action::Inventory => "inventory",
+            Action::Drop => "drop",
+            Action::Unequip => "unequip",
+            Action::CastSpell => "cast_spell",
+            Action::Fire => "fire",
+            Action::OpenDoor => "open_door",
+            Action::CloseDoor => "close_door",
+            Action::Descend => "descend",
+            Action::MessageLog => "message_log",
+            Action::CharacterSheet => "character_sheet",
+            Action::Look => "look",
+            Action::AutoExplore => "auto_explore",
+            Action::TravelToStairs => "travel_to_stairs",
+            Action::Help => "help",
+            Action::SaveAndQuit => "save_and_quit",
+            Action::MenuUp => "menu_up",
+            Action::MenuDown => "menu_down",
+            Action::MenuPageUp => "menu_page_up",
+            Action::MenuPageDown => "menu_page_down",
+            Action::MenuSelect => "menu_select",
+            Action::MenuCancel => "menu_cancel",
+            Action::MenuNext => "menu_next",
+            Action::MenuToggle => "menu_toggle",
+            Action::MenuExport => "menu_export",
+            Action::UseConsumable(slot) => SLOTS[*slot as usize].consumable_setting,
+            Action::QuickCast(slot) => SLOTS[*slot as usize].spell_setting,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveWest => "Move west",
+            Action::MoveEast => "Move east",
+            Action::MoveNorth => "Move north",
+            Action::MoveSouth => "Move south",
+            Action::MoveNorthWest => "Move north west",
+            Action::MoveNorthEast => "Move north east",
+            Action::MoveSouthWest => "Move south west",
+            Action::MoveSouthEast => "Move south east",
+            Action::Wait => "Wait a turn",
+            Action::PickUp => "Pick up an item",
+            Action::Inventory => "Use an item",
+            Action::Drop => "Drop an item",
+            Action::Unequip => "Remove equipment",
+            Action::CastSpell => "Cast a spell",
+            Action::Fire => "Fire a ranged weapon",
+            Action::OpenDoor => "Open a door",
+            Action::CloseDoor => "Close a door",
+            Action::Descend => "Go down the stairs",
+            Action::MessageLog => "Message log",
+            Action::CharacterSheet => "Character sheet",
+            Action::Look => "Look around",
+            Action::AutoExplore => "Explore until something turns up",
+            Action::TravelToStairs => "Travel to the stairs down",
+            Action::Help => "This help screen",
+            Action::SaveAndQuit => "Save and quit",
+            Action::MenuUp => "Up / scroll back",
+            Action::MenuDown => "Down / scroll forward",
+            Action::MenuPageUp => "Page up",
+            Action::MenuPageDown => "Page down",
+            Action::MenuSelect => "Choose",
+            Action::MenuCancel => "Close / cancel",
+            Action::MenuNext => "Next target or filter",
+            Action::MenuToggle => "Switch buying and selling",
+            Action::MenuExport => "Save the message log to a file",
+            Action::UseConsumable(slot) => SLOTS[*slot as usize].consumable_description,
+            Action::QuickCast(slot) => SLOTS[*slot as usize].spell_description,
+        }
+    }
+
+    pub fn direction(&self) -> Option<(i32, i32)> {
+        match self {
+            Action::MoveWest => Some((-1, 0)),
+            Action::MoveEast => Some((1, 0)),
+            Action::MoveNorth => Some((0, -1)),
+            Action::MoveSouth => Some((0, 1)),
+            Action::MoveNorthWest => Some((-1, -1)),
+            Action::MoveNorthEast => Some((1, -1)),
+            Action::MoveSouthWest => Some((-1, 1)),
+            Action::MoveSouthEast => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    fn default_keys(&self) -> &'static str {
+        match self {
+            Action::MoveWest => "Left, H, Numpad4",
+            Action::MoveEast => "Right, L, Numpad6",
+            Action::MoveNorth => "Up, K, Numpad8",
+            Action::MoveSouth => "Down, J, Numpad2",
+            Action::MoveNorthWest => "Y, Numpad7",
+            Action::MoveNorthEast => "U, Numpad9",
+            Action::MoveSouthWest => "B, Numpad1",
+            Action::MoveSouthEast => "N, Numpad3",
+            Action::Wait => "Space, Numpad5",
+            Action::PickUp => "G",
+            Action::Inventory => "I",
+            Action::Drop => "D",
+            Action::Unequip => "R",
+            Action::CastSpell => "Z",
+            Action::Fire => "F",
+            Action::OpenDoor => "O",
+            Action::CloseDoor => "C",
+            Action::Descend => "Period, Shift+Period",
+            Action::MessageLog => "M",
+            Action::CharacterSheet => "P",
+            Action::Look => "X",
+            Action::AutoExplore => "E",
+            Action::TravelToStairs => "T",
+            Action::Help => "F1, Shift+Slash",
+            Action::SaveAndQuit => "Escape",
+            Action::MenuUp => "Up, K, Numpad8",
+            Action::MenuDown => "Down, J, Numpad2",
+            Action::MenuPageUp => "PageUp, Numpad9",
+            Action::MenuPageDown => "PageDown, Numpad3",
+            Action::MenuSelect => "Return, NumpadEnter",
+            Action::MenuCancel => "Escape",
+            Action::MenuNext => "Tab",
+            Action::MenuToggle => "Space",
+            Action::MenuExport => "X",
+            Action::UseConsumable(slot) => SLOTS[*slot as usize].consumable_keys,
+            Action::QuickCast(slot) => SLOTS[*slot as usize].spell_keys,
+        }
+    }
+}
+
+/// Names, descriptions and default keys for the numbered hotkeys: the nth consumable in the pack and
+/// the nth spell the player knows
+struct HotkeySlot {
+    consumable_setting: &'static str,
+    consumable_description: &'static str,
+    consumable_keys: &'static str,
+    spell_setting: &'static str,
+    spell_description: &'static str,
+    spell_keys: &'static str,
+}
+
+macro_rules! hotkey_slot {
+    ($n:literal) => {
+        HotkeySlot {
+            consumable_setting: concat!("use_consumable_", $n),
+            consumable_description: concat!("Use consumable ", $n),
+            consumable_keys: concat!("Shift+Key", $n),
+            spell_setting: concat!("quick_cast_", $n),
+            spell_description: concat!("Cast known spell ", $n),
+            spell_keys: concat!("Ctrl+Key", $n),
+        }
+    };
+}
+
+const SLOTS: [HotkeySlot; 9] = [
+    hotkey_slot!(1),
+    hotkey_slot!(2),
+    hotkey_slot!(3),
+    hotkey_slot!(4),
+    hotkey_slot!(5),
+    hotkey_slot!(6),
+    hotkey_slot!(7),
+    hotkey_slot!(8),
+    hotkey_slot!(9),
+];
+
+/// Keys that can be named in the settings file, by their `VirtualKeyCode` name
+const NAMED_KEYS: [VirtualKeyCode; 78] = [
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+    VirtualKeyCode::Numpad0,
+    VirtualKeyCode::Numpad1,
+    VirtualKeyCode::Numpad2,
+    VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4,
+    VirtualKeyCode::Numpad5,
+    VirtualKeyCode::Numpad6,
+    VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8,
+    VirtualKeyCode::Numpad9,
+    VirtualKeyCode::NumpadAdd,
+    VirtualKeyCode::NumpadSubtract,
+    VirtualKeyCode::NumpadMultiply,
+    VirtualKeyCode::NumpadDivide,
+    VirtualKeyCode::NumpadDecimal,
+    VirtualKeyCode::NumpadEnter,
+    VirtualKeyCode::F1,
+    VirtualKeyCode::F2,
+    VirtualKeyCode::F3,
+    VirtualKeyCode::F4,
+    VirtualKeyCode::F5,
+    VirtualKeyCode::F6,
+    VirtualKeyCode::F7,
+    VirtualKeyCode::F8,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Home,
+    VirtualKeyCode::End,
+    VirtualKeyCode::PageUp,
+    VirtualKeyCode::PageDown,
+    VirtualKeyCode::Insert,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::Escape,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Period,
+    VirtualKeyCode::Comma,
+    VirtualKeyCode::Slash,
+    VirtualKeyCode::Semicolon,
+];
+
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub struct KeyChord {
+    pub key: VirtualKeyCode,
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+}
+
+impl KeyChord {
+    /// Parses chords like `G`, `Shift+Period` or `Ctrl+Alt+Numpad5`
+    pub fn parse(text: &str) -> Result<KeyChord, String> {
+        let mut chord = KeyChord { key: VirtualKeyCode::Escape, shift: false, control: false, alt: false };
+        let mut parts: Vec<&str> = text.split('+').map(|p| p.trim()).collect();
+        let key_name = parts.pop().unwrap_or("");
+        for modifier in parts {
+            match modifier.to_lowercase().as_str() {
+                "shift" => chord.shift = true,
+                "ctrl" | "control" => chord.control = true,
+                "alt" => chord.alt = true,
+                _ => return Err(format!("unknown modifier '{}' in '{}'", modifier, text)),
+            }
+        }
+        chord.key = *NAMED_KEYS
+            .iter()
+            .find(|k| format!("{:?}", k).eq_ignore_ascii_case(key_name))
+            .ok_or_else(|| format!("unknown key '{}'", key_name))?;
+        Ok(chord)
+    }
+
+    fn pressed(ctx: &BTerm) -> Option<KeyChord> {
+        ctx.key.map(|key| KeyChord { key, shift: ctx.shift, control: ctx.control, alt: ctx.alt })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
+fn parse_chords(text: &str) -> (Vec<KeyChord>, Vec<String>) {
+    let mut chords = Vec::new();
+    let mut errors = Vec::new();
+    for part in text.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        match KeyChord::parse(part) {
+            Ok(chord) => chords.push(chord),
+            Err(e) => errors.push(e),
+        }
+    }
+    (chords, errors)
+}
+
+pub struct KeyBindings {
+    bindings: Vec<(Action, Vec<KeyChord>)>,
+}
+
+impl KeyBindings {
+    pub fn default_layout() -> KeyBindings {
+        KeyBindings {
+            bindings: Action::ALL.iter().map(|a| (*a, parse_chords(a.default_keys()).0)).collect(),
+        }
+    }
+
+    /// The default layout with any `key_` settings applied. Also returns a line for each setting that
+    /// couldn't be understood and each key bound to two actions at once.
+    pub fn from_settings(settings: &HashMap<String, String>) -> (KeyBindings, Vec<String>) {
+        let mut bindings = KeyBindings::default_layout();
+        let mut problems = Vec::new();
+        for (name, value) in settings.iter() {
+            let action_name = match name.strip_prefix(SETTINGS_PREFIX) {
+                None => continue,
+                Some(action_name) => action_name,
+            };
+            match bindings.bindings.iter_mut().find(|(a, _)| a.setting_name() == action_name) {
+                None => problems.push(format!("Unknown action in setting '{}'", name)),
+                Some((_, chords)) => {
+                    let (parsed, errors) = parse_chords(value);
+                    problems.extend(errors.into_iter().map(|e| format!("{}: {}", name, e)));
+                    *chords = parsed;
+                }
+            }
+        }
+        problems.extend(bindings.conflicts());
+        (bindings, problems)
+    }
+
+    /// Keys bound to more than one action in the same context. The action listed first wins.
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut seen: HashMap<(KeyChord, BindingContext), Action> = HashMap::new();
+        let mut conflicts = Vec::new();
+        for (action, chords) in self.bindings.iter() {
+            for chord in chords.iter() {
+                match seen.get(&(*chord, action.context())) {
+                    Some(first) if first != action => conflicts.push(format!(
+                        "{} is bound to both {} and {}",
+                        chord,
+                        first.setting_name(),
+                        action.setting_name()
+                    )),
+                    _ => {
+                        seen.insert((*chord, action.context()), *action);
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The action in this context that the key pressed this frame is bound to
+    pub fn action(&self, context: BindingContext, ctx: &BTerm) -> Option<Action> {
+        let pressed = KeyChord::pressed(ctx)?;
+        self.bindings
+            .iter()
+            .find(|(action, chords)| action.context() == context && chords.contains(&pressed))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn pressed(&self, action: Action, ctx: &BTerm) -> bool {
+        KeyChord::pressed(ctx).is_some_and(|pressed| self.chords(action).contains(&pressed))
+    }
+
+    /// The movement direction of the key pressed this frame, if it's bound to one
+    pub fn direction(&self, ctx: &BTerm) -> Option<(i32, i32)> {
+        self.action(BindingContext::Game, ctx).and_then(|a| a.direction())
+    }
+
+    pub fn chords(&self, action: Action) -> &[KeyChord] {
+        self.bindings.iter().find(|(a, _)| *a == action).map_or(&[], |(_, chords)| chords.as_slice())
+    }
+
+    /// The first key for an action, for the short reminders printed at the foot of menus
+    pub fn hint(&self, action: Action) -> String {
+        self.chords(action).first().map_or("(unbound)".to_string(), |c| c.to_string().to_uppercase())
+    }
+
+    pub fn describe(&self, action: Action) -> String {
+        let keys: Vec<String> = self.chords(action).iter().map(|c| c.to_string()).collect();
+        if keys.is_empty() {
+            "(unbound)".to_string()
+        } else {
+            keys.join(", ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chord(key: VirtualKeyCode, shift: bool, control: bool, alt: bool) -> KeyChord {
+        KeyChord { key, shift, control, alt }
+    }
+
+    #[test]
+    fn parses_plain_keys_case_insensitively() {
+        assert_eq!(KeyChord::parse("G"), Ok(chord(VirtualKeyCode::G, false, false, false)));
+        assert_eq!(KeyChord::parse("numpad5"), Ok(chord(VirtualKeyCode::Numpad5, false, false, false)));
+        assert_eq!(KeyChord::parse(" PageUp "), Ok(chord(VirtualKeyCode::PageUp, false, false, false)));
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        assert_eq!(KeyChord::parse("Shift+Period"), Ok(chord(VirtualKeyCode::Period, true, false, false)));
+        assert_eq!(
+            KeyChord::parse("ctrl + alt + Numpad5"),
+            Ok(chord(VirtualKeyCode::Numpad5, false, true, true))
+        );
+        assert_eq!(KeyChord::parse("Control+X"), Ok(chord(VirtualKeyCode::X, false, true, false)));
+    }
+
+    #[test]
+    fn rejects_unknown_keys_and_modifiers() {
+        assert!(KeyChord::parse("").is_err());
+        assert!(KeyChord::parse("Banana").is_err());
+        assert!(KeyChord::parse("Super+G").is_err());
+        assert!(KeyChord::parse("Shift+").is_err());
+    }
+
+    #[test]
+    fn displays_chords_so_they_parse_back() {
+        for text in ["G", "Shift+Period", "Ctrl+Alt+Numpad5", "Ctrl+Alt+Shift+F1"].iter() {
+            let parsed = KeyChord::parse(text).unwrap();
+            assert_eq!(parsed.to_string(), *text);
+            assert_eq!(KeyChord::parse(&parsed.to_string()), Ok(parsed));
+        }
+    }
+
+    #[test]
+    fn every_default_key_parses() {
+        for action in Action::ALL.iter() {
+            let (chords, errors) = parse_chords(action.default_keys());
+            assert!(errors.is_empty(), "{}: {:?}", action.setting_name(), errors);
+            assert!(!chords.is_empty(), "{} has no default keys", action.setting_name());
+        }
+    }
+
+    #[test]
+    fn default_layout_has_no_conflicts() {
+        assert_eq!(KeyBindings::default_layout().conflicts(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn the_same_key_in_different_contexts_is_not_a_conflict() {
+        let bindings = KeyBindings::default_layout();
+        assert_eq!(bindings.chords(Action::SaveAndQuit), bindings.chords(Action::MenuCancel));
+        assert!(bindings.conflicts().is_empty());
+    }
+
+    #[test]
+    fn reports_keys_bound_twice_in_one_context() {
+        let settings: HashMap<String, String> =
+            [("key_pick_up".to_string(), "I".to_string())].iter().cloned().collect();
+        let (bindings, problems) = KeyBindings::from_settings(&settings);
+        assert_eq!(bindings.chords(Action::PickUp), &[chord(VirtualKeyCode::I, false, false, false)]);
+        assert_eq!(problems, vec!["I is bound to both pick_up and inventory".to_string()]);
+    }
+
+    #[test]
+    fn settings_replace_an_actions_default_keys() {
+        let settings: HashMap<String, String> = [
+            ("key_wait".to_string(), "Period, Numpad5".to_string()),
+            ("key_descend".to_string(), "Shift+Period".to_string()),
+            ("volume".to_string(), "11".to_string()),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let (bindings, problems) = KeyBindings::from_settings(&settings);
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(bindings.describe(Action::Wait), "Period, Numpad5");
+        assert_eq!(bindings.describe(Action::Descend), "Shift+Period");
+        assert_eq!(bindings.describe(Action::PickUp), "G");
+    }
+
+    #[test]
+    fn reports_settings_it_cannot_understand() {
+        let settings: HashMap<String, String> = [
+            ("key_dance".to_string(), "Q".to_string()),
+            ("key_fire".to_string(), "F, Banana".to_string()),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        let (bindings, mut problems) = KeyBindings::from_settings(&settings);
+        problems.sort();
+        assert_eq!(
+            problems,
+            vec![
+                "Unknown action in setting 'key_dance'".to_string(),
+                "key_fire: unknown key 'Banana'".to_string(),
+            ]
+        );
+        assert_eq!(bindings.describe(Action::Fire), "F");
+    }
+
+    #[test]
+    fn numbered_hotkeys_can_be_rebound() {
+        let settings: HashMap<String, String> =
+            [("key_quick_cast_2".to_string(), "Alt+Key2".to_string())].iter().cloned().collect();
+        let (bindings, problems) = KeyBindings::from_settings(&settings);
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(bindings.describe(Action::QuickCast(1)), "Alt+Key2");
+        assert_eq!(bindings.describe(Action::UseConsumable(1)), "Shift+Key2");
+    }
+
+    #[test]
+    fn every_action_has_its_own_setting_name() {
+        let mut names: Vec<&str> = Action::ALL.iter().map(|a| a.setting_name()).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), Action::ALL.len());
+    }
+
+    #[test]
+    fn an_empty_setting_unbinds_the_action() {
+        let settings: HashMap<String, String> =
+            [("key_look".to_string(), "".to_string())].iter().cloned().collect();
+        let (bindings, _) = KeyBindings::from_settings(&settings);
+        assert_eq!(bindings.describe(Action::Look), "(unbound)");
+        assert_eq!(bindings.hint(Action::Look), "(unbound)");
+    }
+}
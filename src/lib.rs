@@ -0,0 +1,828 @@
+#[macro_use]
+extern crate lazy_static;
+extern crate strum;
+
+use std::collections::HashMap;
+
+use bracket_lib::color::{RGB, WHITE};
+use bracket_lib::prelude::{console, main_loop, BError, BTerm, BTermBuilder, GameState, Point};
+use bracket_lib::random::RandomNumberGenerator;
+use config::Config;
+use specs::prelude::*;
+use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
+
+use crate::bystander_ai_system::BystanderAI;
+use crate::camera::render_debug_map;
+use crate::components::{
+    AreaOfEffect, Artefact, Asleep, Attributes, Behaviour, BlocksTile, BlocksVisibility, Bystander,
+    Confusion, Consumable, Dialogue, Door, Energy, EntityMoved, EntryTrigger, EquipmentChanged,
+    Equippable, Equipped, Examinable, Faction, Hidden, Home, HungerClock, InBackpack,
+    InflictsDamage, Item, Key, KnownSpell, Locked, MagicMapper, MeleeWeapon, Memory, Monster,
+    MyTurn, Name, NaturalAttackDefense, OpensDoors, Overloaded, PackMember, ParticleLifetime,
+    Player, Pools, Position, ProvidesFood, ProvidesHealing, Quests, Quips, Ranged, RangedWeapon,
+    Regeneration, Renderable, Reputation, Schedule, SerializationHelper, SerializeMe, Shield,
+    SingleActivation, Skills, SufferDamage, TeachesSpell, TwoHanded, Vendor, Viewshed,
+    WantsToCastSpell, WantsToDropItem, WantsToMelee, WantsToPickUpItem, WantsToShoot,
+    WantsToUnequipItem, WantsToUseItem, Wearable,
+};
+use crate::damage_system::DamageSystem;
+use crate::encumbrance_system::EncumbranceSystem;
+use crate::energy_system::EnergySystem;
+use crate::flow_map_system::FlowMapSystem;
+use crate::events::{GameEvent, GameEvents};
+use crate::gameclock::GameClock;
+use crate::gamelog::{GameLog, LogCategory, LOG_EXPORT_FILE};
+use crate::gui::ItemMenuResult::NoResponse;
+use crate::gui::{
+    cast_spell_menu, drop_item_menu, ranged_target, show_inventory, GameOverResult, ItemMenuResult, LogViewResult,
+    MainMenuResult, MainMenuSelection, VendorMode, VendorResult,
+};
+use crate::hunger_system::HungerSystem;
+use crate::inventory_system::{
+    ItemCollectionSystem, ItemDropSystem, ItemUnequippingSystem, ItemUseSystem,
+};
+use crate::keybindings::{Action, KeyBindings};
+use crate::map::Map;
+use crate::map_indexing_system::MapIndexingSystem;
+use crate::melee_combat_system::MeleeCombatSystem;
+use crate::monster_ai_system::MonsterAI;
+use crate::particle_system::ParticleSpawnSystem;
+use crate::ranged_combat_system::RangedCombatSystem;
+use crate::regen_system::RegenSystem;
+use crate::player::{apply_level_up, door_direction_input, has_pending_level_up, look_input, player_input};
+use crate::rex_assets::RexAssets;
+use crate::spawner::{player, PackIds};
+use crate::sound_system::{EventNoiseSystem, HearingSystem};
+use crate::spell_system::{player_can_cast, SpellCastingSystem};
+use crate::statistics_system::{Statistics, StatisticsSystem};
+use crate::travel::Travel;
+use crate::trigger_system::TriggerSystem;
+use crate::vendor_system::{buy_item, sell_item, VendorSystem};
+use crate::visibility_system::VisibilitySystem;
+use crate::RunState::MainMenu;
+
+mod bystander_ai_system;
+mod camera;
+pub mod combat_sim;
+mod components;
+mod damage_system;
+mod dialogue;
+mod doors;
+mod encumbrance_system;
+mod energy_system;
+mod events;
+mod flow_map_system;
+mod gameclock;
+mod gamelog;
+mod gamesystem;
+mod gui;
+mod hunger_system;
+mod inventory_system;
+mod keybindings;
+mod map;
+pub mod map_builders;
+mod map_indexing_system;
+mod melee_combat_system;
+mod monster_ai_system;
+mod particle_system;
+mod player;
+mod random_tables;
+mod ranged_combat_system;
+mod raws;
+mod rect;
+mod regen_system;
+mod rex_assets;
+mod saveload_system;
+mod spawner;
+mod sound_system;
+mod spell_system;
+mod statistics_system;
+mod travel;
+mod trigger_system;
+mod vendor_system;
+mod visibility_system;
+
+mod util {
+    pub mod config;
+    pub mod namegen;
+    pub mod string_utils;
+}
+
+const SHOW_MAPGEN_VISUALIZATION: bool = false;
+const DEBUGGING: bool = false;
+
+const SCREEN_X: i32 = 120;
+const SCREEN_Y: i32 = 100;
+
+const MAP_X: i32 = 100;
+const MAP_Y: i32 = 72;
+
+lazy_static! {
+    static ref configs: HashMap<String, String> = Config::builder()
+        .add_source(config::File::with_name("settings"))
+        .build()
+        .unwrap()
+        .try_deserialize::<HashMap<String, String>>()
+        .unwrap();
+    static ref SCREEN_X_REF: i32 = configs.get("screen_x").unwrap().parse().unwrap();
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum RunState {
+    AwaitingInput,
+    PreRun,
+    PlayerTurn,
+    MonsterTurn,
+    ShowInventory,
+    ShowDropItem,
+    ShowTargeting { range: i32, item: Entity },
+    ShowCastSpell,
+    MainMenu { menu_selection: MainMenuSelection },
+    SaveGame,
+    NextLevel,
+    ShowRemoveItem,
+    GameOver,
+    MagicMapReveal { row: i32 },
+    LevelUp,
+    // MapGeneration,
+    ShowMapVisualization,
+    ShowDialogue { speaker: Entity, node: usize },
+    ShowVendor { vendor: Entity, mode: VendorMode },
+    ChooseDoor { open: bool },
+    ShowLog { scroll: usize, filter: Option<LogCategory> },
+    CharacterSheet,
+    Look { x: i32, y: i32 },
+    ShowHelp,
+}
+
+struct State {
+    ecs: World,
+    mapgen_next_state: Option<RunState>,
+    mapgen_history: Vec<Map>,
+    mapgen_index: usize,
+    mapgen_timer: f32,
+}
+
+impl State {
+    fn run_systems(&mut self) {
+        let mut energy = EnergySystem {};
+        energy.run_now(&self.ecs);
+        self.ecs.write_resource::<GameLog>().turn = self.ecs.fetch::<GameClock>().turn();
+        let mut vis = VisibilitySystem {};
+        vis.run_now(&self.ecs);
+        let mut hearing = HearingSystem {};
+        hearing.run_now(&self.ecs);
+        let mut flow_maps = FlowMapSystem {};
+        flow_maps.run_now(&self.ecs);
+        let mut mob = MonsterAI {};
+        mob.run_now(&self.ecs);
+        let mut bystandar_ai = BystanderAI {};
+        bystandar_ai.run_now(&self.ecs);
+        let mut vendors = VendorSystem {};
+        vendors.run_now(&self.ecs);
+        let mut triggers = TriggerSystem {};
+        triggers.run_now(&self.ecs);
+        let mut mapindex = MapIndexingSystem {};
+        mapindex.run_now(&self.ecs);
+        let mut melee_combat_sys = MeleeCombatSystem {};
+        melee_combat_sys.run_now(&self.ecs);
+        let mut ranged_combat_sys = RangedCombatSystem {};
+        ranged_combat_sys.run_now(&self.ecs);
+        let mut damage_system = DamageSystem {};
+        damage_system.run_now(&self.ecs);
+        let mut pickup = ItemCollectionSystem {};
+        pickup.run_now(&self.ecs);
+        let mut spell_casting_system = SpellCastingSystem {};
+        spell_casting_system.run_now(&self.ecs);
+        let mut potion_use_system = ItemUseSystem {};
+        potion_use_system.run_now(&self.ecs);
+        let mut item_drop_system = ItemDropSystem {};
+        item_drop_system.run_now(&self.ecs);
+        let mut item_unequipping_system = ItemUnequippingSystem {};
+        item_unequipping_system.run_now(&self.ecs);
+        let mut encumbrance_system = EncumbranceSystem {};
+        encumbrance_system.run_now(&self.ecs);
+        let mut particle_system = ParticleSpawnSystem {};
+        particle_system.run_now(&self.ecs);
+        let mut hunger_system = HungerSystem {};
+        hunger_system.run_now(&self.ecs);
+        let mut regen_system = RegenSystem {};
+        regen_system.run_now(&self.ecs);
+        // Subscribers to the event bus go last, so they hear about everything that happened
+        let mut statistics = StatisticsSystem {};
+        statistics.run_now(&self.ecs);
+        let mut event_noise = EventNoiseSystem {};
+        event_noise.run_now(&self.ecs);
+        self.ecs.write_resource::<GameEvents>().clear();
+        self.ecs.maintain();
+    }
+
+    fn generate_world_map(&mut self, new_depth: i32) {
+        self.mapgen_index = 0;
+        self.mapgen_timer = 0.0;
+        self.mapgen_history.clear();
+        let mut rng = self.ecs.write_resource::<RandomNumberGenerator>();
+        let mut builder = map_builders::level_builder(new_depth, &mut rng, MAP_X, MAP_Y);
+        builder.build_map(&mut rng);
+        drop(rng);
+        self.mapgen_history = builder.build_data.history.clone();
+        let player_start;
+        {
+            let mut map_resource = self.ecs.write_resource::<Map>();
+            *map_resource = builder.build_data.map.clone();
+            player_start = builder
+                .build_data
+                .starting_position
+                .as_mut()
+                .unwrap()
+                .clone();
+        }
+        builder.spawn_entities(&mut self.ecs);
+        let (player_x, player_y) = (player_start.x, player_start.y);
+        self.ecs
+            .write_resource::<GameEvents>()
+            .publish(GameEvent::LevelEntered { depth: new_depth, position: Point::new(player_x, player_y) });
+        let mut player_pos = self.ecs.write_resource::<Point>();
+        *player_pos = Point::new(player_x, player_y);
+        let mut position_component = self.ecs.write_storage::<Position>();
+        let player_entity = self.ecs.fetch::<Entity>();
+        let player_pos_comp = position_component.get_mut(*player_entity);
+        if let Some(player_pos_comp) = player_pos_comp {
+            player_pos_comp.x = player_x;
+            player_pos_comp.y = player_y;
+        }
+        let mut viewshed_comp = self.ecs.write_storage::<Viewshed>();
+        let vs = viewshed_comp.get_mut(*player_entity);
+        if let Some(vs) = vs {
+            vs.dirty = true;
+        }
+    }
+
+    fn entities_to_remove_on_level_change(&mut self) -> Vec<Entity> {
+        let entities = self.ecs.entities();
+        let player = self.ecs.read_storage::<Player>();
+        let backpack = self.ecs.read_storage::<InBackpack>();
+        let player_entity = self.ecs.fetch::<Entity>();
+        let equipped = self.ecs.read_storage::<Equipped>();
+        let known_spells = self.ecs.read_storage::<KnownSpell>();
+
+        let mut to_delete: Vec<Entity> = Vec::new();
+        for entity in entities.join() {
+            let mut should_delete = true;
+            let p = player.get(entity);
+            if let Some(_p) = p {
+                should_delete = false;
+            }
+            let bp = backpack.get(entity);
+            if let Some(bp) = bp {
+                if bp.owner == *player_entity {
+                    should_delete = false;
+                }
+            }
+            let eq = equipped.get(entity);
+            if let Some(eq) = eq {
+                if eq.owner == *player_entity {
+                    should_delete = false;
+                }
+            }
+            let ks = known_spells.get(entity);
+            if let Some(ks) = ks {
+                if ks.owner == *player_entity {
+                    should_delete = false;
+                }
+            }
+            if should_delete {
+                to_delete.push(entity);
+            }
+        }
+        to_delete
+    }
+
+    pub fn game_over_cleanup(&mut self) {
+        let mut to_delete = Vec::new();
+        for e in self.ecs.entities().join() {
+            to_delete.push(e);
+        }
+        for del in to_delete.iter() {
+            self.ecs.delete_entity(*del).expect("Could not delete")
+        }
+        {
+            let p_entity = player(&mut self.ecs, 0, 0);
+            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
+            *player_entity_writer = p_entity;
+        }
+        *self.ecs.write_resource::<GameClock>() = GameClock::new();
+        *self.ecs.write_resource::<Statistics>() = Statistics::default();
+        *self.ecs.write_resource::<PackIds>() = PackIds::default();
+        self.ecs.write_resource::<GameLog>().clear();
+        self.ecs.write_resource::<Travel>().stop();
+        self.ecs.write_resource::<GameEvents>().clear();
+        self.generate_world_map(1);
+    }
+
+    fn goto_next_level(&mut self) {
+        let to_delete = self.entities_to_remove_on_level_change();
+        for target in to_delete {
+            self.ecs
+                .delete_entity(target)
+                .expect("Unable to delete entity");
+        }
+
+        let current_depth;
+        {
+            let worldmap_resources = self.ecs.write_resource::<Map>();
+            current_depth = worldmap_resources.depth;
+        }
+        self.generate_world_map(current_depth + 1);
+
+        let mut gamelog = self.ecs.fetch_mut::<GameLog>();
+        gamelog.add(LogCategory::System, "You descend to the next level");
+    }
+}
+
+impl GameState for State {
+    fn tick(&mut self, ctx: &mut BTerm) {
+        let mut new_runstate;
+        {
+            let runstate = self.ecs.fetch::<RunState>();
+            new_runstate = *runstate;
+        }
+        ctx.cls();
+        particle_system::cull_dead_particles(&mut self.ecs, ctx);
+        match new_runstate {
+            RunState::MainMenu { .. } => {}
+            RunState::GameOver { .. } => {}
+            _ => {
+                camera::render_camera(&self.ecs, ctx);
+                gui::draw_ui(&self.ecs, ctx);
+            }
+        }
+        match new_runstate {
+            RunState::ShowMapVisualization => {
+                if !SHOW_MAPGEN_VISUALIZATION {
+                    new_runstate = self.mapgen_next_state.unwrap();
+                }
+                ctx.cls();
+                if self.mapgen_index < self.mapgen_history.len() {
+                    let map = &self.mapgen_history[self.mapgen_index];
+                    render_debug_map(map, ctx);
+                }
+
+                self.mapgen_timer += ctx.frame_time_ms;
+                if self.mapgen_timer > 50.0 {
+                    self.mapgen_timer = 0.0;
+                    self.mapgen_index += 1;
+                    if self.mapgen_index >= self.mapgen_history.len() {
+                        new_runstate = self.mapgen_next_state.unwrap();
+                    }
+                }
+            }
+
+            MainMenu { .. } => {
+                let result = gui::main_menu(self, ctx);
+                match result {
+                    MainMenuResult::NoSelection { selected } => {
+                        new_runstate = MainMenu {
+                            menu_selection: selected,
+                        }
+                    }
+                    MainMenuResult::Selected { selected } => match selected {
+                        MainMenuSelection::NewGame => new_runstate = RunState::PreRun,
+                        MainMenuSelection::LoadGame => {
+                            saveload_system::load_game(&mut self.ecs);
+                            new_runstate = RunState::AwaitingInput;
+                            saveload_system::delete_save();
+                        }
+                        MainMenuSelection::Quit => {
+                            std::process::exit(0);
+                        }
+                    },
+                }
+            }
+            RunState::PreRun => {
+                self.run_systems();
+                self.ecs.maintain();
+                if (SHOW_MAPGEN_VISUALIZATION) {
+                    new_runstate = RunState::ShowMapVisualization;
+                    self.mapgen_next_state = Some(RunState::AwaitingInput);
+                } else {
+                    new_runstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::AwaitingInput => {
+                if has_pending_level_up(&self.ecs) {
+                    new_runstate = RunState::LevelUp;
+                } else if let Some(state) = travel::continue_travel(&mut self.ecs, ctx) {
+                    new_runstate = state;
+                } else {
+                    new_runstate = player_input(self, ctx);
+                }
+            }
+            RunState::PlayerTurn => {
+                self.run_systems();
+                self.ecs.maintain();
+                match *self.ecs.fetch::<RunState>() {
+                    RunState::MagicMapReveal { .. } => {
+                        new_runstate = RunState::MagicMapReveal { row: 0 }
+                    }
+                    _ => new_runstate = RunState::MonsterTurn,
+                }
+            }
+            RunState::MonsterTurn => {
+                // Keep ticking until the player has built up enough energy to act again
+                loop {
+                    {
+                        let mut runwriter = self.ecs.write_resource::<RunState>();
+                        *runwriter = RunState::MonsterTurn;
+                    }
+                    self.run_systems();
+                    self.ecs.maintain();
+                    if DamageSystem::delete_the_dead(&mut self.ecs) {
+                        let mut mapindex = MapIndexingSystem {};
+                        mapindex.run_now(&self.ecs);
+                    }
+                    let current = *self.ecs.fetch::<RunState>();
+                    if current != RunState::MonsterTurn {
+                        new_runstate = current;
+                        break;
+                    }
+                }
+            }
+            RunState::ShowInventory => {
+                let result = show_inventory(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_item_ranged = is_ranged.get(item);
+                        if let Some(is_item_ranged) = is_item_ranged {
+                            new_runstate = RunState::ShowTargeting {
+                                range: is_item_ranged.range,
+                                item,
+                            };
+                        } else {
+                            let mut wants_to_use = self.ecs.write_storage::<WantsToUseItem>();
+                            wants_to_use
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem { item, target: None },
+                                )
+                                .expect("Unable to consume potion");
+                            new_runstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowCastSpell => {
+                let result = cast_spell_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let spell = result.1.unwrap();
+                        let is_ranged = self.ecs.read_storage::<Ranged>();
+                        let is_spell_ranged = is_ranged.get(spell);
+                        if !player_can_cast(&self.ecs, spell) {
+                            new_runstate = RunState::AwaitingInput;
+                        } else if let Some(is_spell_ranged) = is_spell_ranged {
+                            new_runstate = RunState::ShowTargeting {
+                                range: is_spell_ranged.range,
+                                item: spell,
+                            };
+                        } else {
+                            let mut wants_to_cast = self.ecs.write_storage::<WantsToCastSpell>();
+                            wants_to_cast
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToCastSpell { spell, target: None },
+                                )
+                                .expect("Unable to cast spell");
+                            new_runstate = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowDropItem => {
+                let result = drop_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut drop = self.ecs.write_storage::<WantsToDropItem>();
+                        drop.insert(
+                            *self.ecs.fetch::<Entity>(),
+                            WantsToDropItem { item: item_entity },
+                        )
+                        .expect("Unable to insert drop item");
+                        new_runstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowTargeting { range, item } => {
+                let result = ranged_target(self, ctx, range);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        if self.ecs.read_storage::<RangedWeapon>().get(item).is_some() {
+                            if let Some(target) = result.1 {
+                                self.ecs
+                                    .write_storage::<WantsToShoot>()
+                                    .insert(*self.ecs.fetch::<Entity>(), WantsToShoot { target })
+                                    .expect("Cannot insert WantsToShoot");
+                            }
+                        } else if self.ecs.read_storage::<KnownSpell>().get(item).is_some() {
+                            let mut cast_spell = self.ecs.write_storage::<WantsToCastSpell>();
+                            cast_spell
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToCastSpell {
+                                        spell: item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Cannot insert WantsToCastSpell");
+                        } else {
+                            let mut use_item = self.ecs.write_storage::<WantsToUseItem>();
+                            use_item
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToUseItem {
+                                        item,
+                                        target: result.1,
+                                    },
+                                )
+                                .expect("Cannot insert WantsToUseItem");
+                        }
+                        new_runstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::SaveGame => {
+                saveload_system::save_game(&mut self.ecs);
+                new_runstate = RunState::MainMenu {
+                    menu_selection: MainMenuSelection::LoadGame,
+                };
+            }
+            RunState::NextLevel => {
+                self.goto_next_level();
+                new_runstate = RunState::PreRun;
+            }
+            RunState::ShowRemoveItem => {
+                let result = gui::unequip_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToUnequipItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToUnequipItem { item: item_entity },
+                            )
+                            .expect("Unable to insert intent");
+                        new_runstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::GameOver => {
+                let result = gui::game_over(ctx, &self.ecs.fetch::<Statistics>());
+                match result {
+                    GameOverResult::NoSelection => {}
+                    GameOverResult::QuitToMenu => {
+                        self.game_over_cleanup();
+                        new_runstate = RunState::MainMenu {
+                            menu_selection: MainMenuSelection::NewGame,
+                        };
+                    }
+                }
+            }
+            RunState::Look { x, y } => {
+                gui::draw_look(&self.ecs, ctx, x, y);
+                new_runstate = look_input(self, ctx, x, y);
+            }
+            RunState::ShowHelp => {
+                if gui::show_help(self, ctx) == ItemMenuResult::Cancel {
+                    new_runstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::CharacterSheet => {
+                if gui::show_character_sheet(self, ctx) == ItemMenuResult::Cancel {
+                    new_runstate = RunState::AwaitingInput;
+                }
+            }
+            RunState::ShowLog { scroll, filter } => match gui::show_log(self, ctx, scroll, filter) {
+                LogViewResult::NoResponse => {}
+                LogViewResult::Close => new_runstate = RunState::AwaitingInput,
+                LogViewResult::Scroll(scroll) => new_runstate = RunState::ShowLog { scroll, filter },
+                LogViewResult::Filter(filter) => new_runstate = RunState::ShowLog { scroll: 0, filter },
+                LogViewResult::Export => {
+                    let mut gamelog = self.ecs.fetch_mut::<GameLog>();
+                    match gamelog.export(LOG_EXPORT_FILE) {
+                        Ok(()) => gamelog.add(LogCategory::System, format!("Message log saved to {}", LOG_EXPORT_FILE)),
+                        Err(e) => gamelog.add(LogCategory::System, format!("Couldn't save the message log: {}", e)),
+                    }
+                }
+            },
+            RunState::ChooseDoor { open } => {
+                new_runstate = door_direction_input(self, ctx, open);
+            }
+            RunState::ShowDialogue { speaker, node } => {
+                let result = gui::show_dialogue(self, ctx, speaker, node);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_runstate = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        new_runstate = dialogue::choose(&mut self.ecs, speaker, node, result.1.unwrap());
+                    }
+                }
+            }
+            RunState::ShowVendor { vendor, mode } => match gui::show_vendor(self, ctx, vendor, mode) {
+                VendorResult::NoResponse => {}
+                VendorResult::Cancel => new_runstate = RunState::AwaitingInput,
+                VendorResult::ToggleMode => {
+                    let mode = match mode {
+                        VendorMode::Buy => VendorMode::Sell,
+                        VendorMode::Sell => VendorMode::Buy,
+                    };
+                    new_runstate = RunState::ShowVendor { vendor, mode };
+                }
+                VendorResult::Buy(stock_index) => buy_item(&mut self.ecs, vendor, stock_index),
+                VendorResult::Sell(item) => sell_item(&mut self.ecs, vendor, item),
+            },
+            RunState::LevelUp => {
+                let result = gui::level_up_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel | ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        apply_level_up(&mut self.ecs, result.1.unwrap());
+                        new_runstate = RunState::AwaitingInput;
+                    }
+                }
+            }
+            RunState::MagicMapReveal { row } => {
+                let mut map = self.ecs.fetch_mut::<Map>();
+                for x in 0..map.width {
+                    map.revealed_tiles[x as usize][row as usize] = true;
+                }
+                if row == map.height - 1 {
+                    new_runstate = RunState::MonsterTurn;
+                } else {
+                    new_runstate = RunState::MagicMapReveal { row: row + 1 }
+                }
+            }
+        }
+        {
+            let mut runwriter = self.ecs.write_resource::<RunState>();
+            *runwriter = new_runstate;
+        }
+        if DamageSystem::delete_the_dead(&mut self.ecs) {
+            let mut mapindex = MapIndexingSystem {};
+            mapindex.run_now(&self.ecs);
+        }
+    }
+}
+
+fn register_components(ecs: &mut World) {
+    ecs.register::<Position>();
+    ecs.register::<Renderable>();
+    ecs.register::<Player>();
+    ecs.register::<Viewshed>();
+    ecs.register::<Monster>();
+    ecs.register::<Behaviour>();
+    ecs.register::<Home>();
+    ecs.register::<Memory>();
+    ecs.register::<Asleep>();
+    ecs.register::<Faction>();
+    ecs.register::<Reputation>();
+    ecs.register::<RangedWeapon>();
+    ecs.register::<WantsToShoot>();
+    ecs.register::<PackMember>();
+    ecs.register::<Dialogue>();
+    ecs.register::<Quests>();
+    ecs.register::<Schedule>();
+    ecs.register::<Bystander>();
+    ecs.register::<Vendor>();
+    ecs.register::<Name>();
+    ecs.register::<BlocksTile>();
+    ecs.register::<WantsToMelee>();
+    ecs.register::<SufferDamage>();
+    ecs.register::<Item>();
+    ecs.register::<ProvidesHealing>();
+    ecs.register::<InBackpack>();
+    ecs.register::<WantsToPickUpItem>();
+    ecs.register::<WantsToUseItem>();
+    ecs.register::<WantsToDropItem>();
+    ecs.register::<Artefact>();
+    ecs.register::<Consumable>();
+    ecs.register::<Ranged>();
+    ecs.register::<InflictsDamage>();
+    ecs.register::<AreaOfEffect>();
+    ecs.register::<Confusion>();
+    ecs.register::<SimpleMarker<SerializeMe>>();
+    ecs.register::<SerializationHelper>();
+    ecs.register::<Examinable>();
+    ecs.register::<Equippable>();
+    ecs.register::<Equipped>();
+    ecs.register::<MeleeWeapon>();
+    ecs.register::<Wearable>();
+    ecs.register::<WantsToUnequipItem>();
+    ecs.register::<ParticleLifetime>();
+    ecs.register::<HungerClock>();
+    ecs.register::<ProvidesFood>();
+    ecs.register::<MagicMapper>();
+    ecs.register::<Hidden>();
+    ecs.register::<EntryTrigger>();
+    ecs.register::<EntityMoved>();
+    ecs.register::<SingleActivation>();
+    ecs.register::<BlocksVisibility>();
+    ecs.register::<Door>();
+    ecs.register::<Locked>();
+    ecs.register::<Key>();
+    ecs.register::<OpensDoors>();
+    ecs.register::<Quips>();
+    ecs.register::<Attributes>();
+    ecs.register::<Skills>();
+    ecs.register::<Pools>();
+    ecs.register::<NaturalAttackDefense>();
+    ecs.register::<Energy>();
+    ecs.register::<MyTurn>();
+    ecs.register::<KnownSpell>();
+    ecs.register::<WantsToCastSpell>();
+    ecs.register::<TeachesSpell>();
+    ecs.register::<EquipmentChanged>();
+    ecs.register::<Overloaded>();
+    ecs.register::<Shield>();
+    ecs.register::<TwoHanded>();
+    ecs.register::<Regeneration>();
+}
+
+pub fn run() -> BError {
+    println!("Hello, world!");
+    let mut state = State {
+        ecs: World::new(),
+        mapgen_next_state: Some(MainMenu {
+            menu_selection: MainMenuSelection::NewGame,
+        }),
+        mapgen_index: 0,
+        mapgen_history: Vec::new(),
+        mapgen_timer: 0.0,
+    };
+    register_components(&mut state.ecs);
+
+    raws::load_raws();
+
+    state.ecs.insert(particle_system::ParticleBuilder::new());
+    state
+        .ecs
+        .insert(SimpleMarkerAllocator::<SerializeMe>::new());
+    state.ecs.insert(RexAssets::new());
+    state.ecs.insert(RandomNumberGenerator::new());
+    state.ecs.insert(sound_system::Sounds::default());
+    state.ecs.insert(flow_map_system::FlowMaps::default());
+    state.ecs.insert(GameClock::new());
+    state.ecs.insert(GameEvents::default());
+    state.ecs.insert(Statistics::default());
+    state.ecs.insert(PackIds::default());
+
+    state.ecs.insert(Map::new(1, 64, 64, "New Map"));
+    state.ecs.insert(Point::new(0, 0));
+    let player_entity = player(&mut state.ecs, 0, 0);
+    state.ecs.insert(player_entity);
+    state.ecs.insert(RunState::MainMenu {
+        menu_selection: MainMenuSelection::NewGame,
+    });
+    let mut gamelog = GameLog::new();
+    gamelog.add(LogCategory::System, "Welcome to the Halls of Ruztoo");
+    let (bindings, problems) = KeyBindings::from_settings(&configs);
+    for problem in problems.iter() {
+        println!("Key bindings: {}", problem);
+        gamelog.add(LogCategory::System, format!("Key bindings: {}", problem));
+    }
+    gamelog.add(
+        LogCategory::System,
+        format!("Press {} for a list of commands", bindings.hint(Action::Help)),
+    );
+    state.ecs.insert(gamelog);
+    state.ecs.insert(bindings);
+    state.ecs.insert(Travel::default());
+
+    state.generate_world_map(1);
+
+    println!("{:?}", *configs);
+
+    let mut bterm = BTermBuilder::simple(SCREEN_X, SCREEN_Y)?
+        .with_title("Rusty Roguelike V2")
+        .with_tile_dimensions(8, 8)
+        .with_fps_cap(120.)
+        .with_fitscreen(true)
+        .build()?;
+    bterm.with_post_scanlines(true);
+    bterm.screen_burn_color(RGB::named(WHITE));
+
+    main_loop(bterm, state)
+}
@@ -1,5 +1,5 @@
 use core::f32;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::usize;
 
 use bracket_lib::algorithm_traits::{Algorithm2D, BaseMap, SmallVec};
@@ -10,9 +10,10 @@ use bracket_lib::prelude::{to_cp437, BTerm, FontCharType};
 use serde::{Deserialize, Serialize};
 use specs::Entity;
 
-pub use tiletype::{tile_opaque, tile_walkable, TileType};
+pub use tiletype::{tile_name, tile_opaque, tile_walkable, TileType};
 
 use crate::map::tiletype::tile_cost;
+use crate::rect::Rect;
 
 pub mod tiletype;
 
@@ -29,6 +30,7 @@ pub struct Map {
     pub view_blocked: HashSet<(i32, i32)>,
     pub name: String,
     pub noise: Vec<Vec<f32>>,
+    pub landmarks: HashMap<String, Vec<Rect>>,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -123,6 +125,7 @@ impl Map {
             view_blocked: HashSet::new(),
             name: name.to_string(),
             noise: vec![vec![0.; height as usize]; width as usize],
+            landmarks: HashMap::new(),
         }
     }
     pub fn xy_idx(&self, x: i32, y: i32) -> usize {
@@ -45,3 +45,19 @@ pub fn tile_cost(tt: TileType) -> f32 {
         _ => 1.0,
     }
 }
+
+pub fn tile_name(tt: TileType) -> &'static str {
+    match tt {
+        TileType::Wall => "A wall",
+        TileType::Floor => "Stone floor",
+        TileType::DownStairs => "Stairs leading down",
+        TileType::Road => "A road",
+        TileType::Grass => "Grass",
+        TileType::ShallowWater => "Shallow water",
+        TileType::DeepWater => "Deep water",
+        TileType::WoodFloor => "Wooden floor",
+        TileType::Bridge => "A bridge",
+        TileType::Gravel => "Gravel",
+        TileType::Moss => "Moss",
+    }
+}
@@ -1,13 +1,19 @@
-use bracket_lib::prelude::RandomNumberGenerator;
+use std::collections::HashSet;
+
+use bracket_lib::prelude::{DijkstraMap, RandomNumberGenerator};
 
 use crate::map::tiletype::TileType;
 use crate::map_builders::{BuilderMap, MetaMapBuilder};
 
+const LOCKED_DOOR: &str = "LockedDoor";
+const KEY: &str = "IronKey";
+
 pub struct DoorPlacement {}
 
 impl MetaMapBuilder for DoorPlacement {
     fn build_map(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
         self.door(rng, build_data);
+        self.lock_doors(rng, build_data);
     }
 }
 
@@ -39,7 +45,65 @@ impl DoorPlacement {
         }
     }
 
+    // From the second level down some doors are locked, and a key that opens them is left somewhere
+    // the player can get to without going through any of them
+    fn lock_doors(&mut self, rng: &mut RandomNumberGenerator, build_data: &mut BuilderMap) {
+        let start = match &build_data.starting_position {
+            Some(start) if build_data.map.depth > 1 => (start.x, start.y),
+            _ => return,
+        };
+        let mut locked = HashSet::new();
+        for spawn in build_data.spawn_list.iter_mut() {
+            if spawn.1 == "Door" && rng.roll_dice(1, 4) == 1 {
+                spawn.1 = LOCKED_DOOR.to_string();
+                locked.insert(spawn.0);
+            }
+        }
+        if locked.is_empty() {
+            return;
+        }
+
+        build_data.map.populate_blocked();
+        for (x, y) in locked.iter() {
+            build_data.map.blocked[*x as usize][*y as usize] = true;
+        }
+        let start_idx = build_data.map.xy_idx(start.0, start.1);
+        let dijkstra_map = DijkstraMap::new(build_data.map.width, build_data.map.height, &[start_idx], &build_data.map, 1000.0);
+        let taken: HashSet<(i32, i32)> = build_data.spawn_list.iter().map(|spawn| spawn.0).collect();
+        let mut spots = Vec::new();
+        for (x, row) in build_data.map.tiles.iter().enumerate() {
+            for (y, tile) in row.iter().enumerate() {
+                let spot = (x as i32, y as i32);
+                if *tile == TileType::Floor
+                    && spot != start
+                    && !taken.contains(&spot)
+                    && dijkstra_map.map[y * build_data.map.width as usize + x] < f32::MAX
+                {
+                    spots.push(spot);
+                }
+            }
+        }
+        build_data.map.populate_blocked();
+
+        if spots.is_empty() {
+            for spawn in build_data.spawn_list.iter_mut() {
+                if spawn.1 == LOCKED_DOOR {
+                    spawn.1 = "Door".to_string();
+                }
+            }
+            return;
+        }
+        let spot = spots[(rng.roll_dice(1, spots.len() as i32) - 1) as usize];
+        build_data.spawn_list.push((spot, KEY.to_string()));
+    }
+
     fn door_possible(&self, build_data: &mut BuilderMap, x: usize, y: usize) -> bool {
+        for spawn in build_data.spawn_list.iter() {
+            if spawn.0.0 == x as i32 && spawn.0.1 == y as i32 {
+                return false;
+            }
+        }
+
         if build_data.map.tiles[x][y] == TileType::Floor &&
             (x > 1 && build_data.map.tiles[x-1][y] == TileType::Floor) &&
             (x < (build_data.map.width - 2) as usize && build_data.map.tiles[x+1][y] == TileType::Floor) &&
@@ -55,11 +119,6 @@ impl DoorPlacement {
             (x < (build_data.map.height - 2) as usize && build_data.map.tiles[x][y+1] == TileType::Floor) {
             return true;
         }
-        for spawn in build_data.spawn_list.iter() {
-            if spawn.0.0 == x as i32 && spawn.0.1 == y as i32 {
-                return false;
-            }
-        }
 
         false
     }
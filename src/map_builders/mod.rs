@@ -14,6 +14,7 @@ use crate::map_builders::cull_unreachable::CullUnreachable;
 use crate::map_builders::debug_map::DebugMapBuilder;
 use crate::map_builders::distant_exit::DistantExit;
 use crate::map_builders::dla::DLABuilder;
+use crate::map_builders::door_placement::DoorPlacement;
 use crate::map_builders::drunkards::DrunkardsWalkBuilder;
 use crate::map_builders::maze::MazeBuilder;
 use crate::map_builders::noise::NoiseBuilder;
@@ -338,6 +339,7 @@ pub fn random_builder(
     // builder.with(DistantExit::new());
     // builder.with(DoorPlacement::new());
     //
+    builder.with(DoorPlacement::new());
     builder.with(NoiseBuilder::new(NoiseType::WhiteNoise));
     builder.with(NoiseVegitationBuilder::new());
 
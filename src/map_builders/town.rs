@@ -6,6 +6,7 @@ use bracket_lib::random::RandomNumberGenerator;
 
 use crate::components::Position;
 use crate::map::TileType;
+use crate::rect::Rect;
 use crate::map::TileType::{Bridge, DownStairs, Floor, Road, Wall, WoodFloor};
 use crate::map_builders::{
     distant_exit::DistantExit, random_start_position, BuilderChain, BuilderMap, InitialMapBuilder,
@@ -309,6 +310,13 @@ impl TownBuilder {
         for (_i, b_idx) in building_index.iter().enumerate() {
             let build_type = &b_idx.2;
             let building = buildings[b_idx.0];
+            // Remembered so that townsfolk can find their way to work, the pub and home again
+            build_data
+                .map
+                .landmarks
+                .entry(format!("{:?}", build_type))
+                .or_default()
+                .push(Rect::new(building.0, building.1, building.2, building.3));
             match build_type {
                 BuildingTag::Pub => {
                     console::log(format!(
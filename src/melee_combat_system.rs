@@ -3,11 +3,14 @@ use bracket_lib::prelude::to_cp437;
 use bracket_lib::random::RandomNumberGenerator;
 use specs::prelude::*;
 
-use crate::components::{Attributes, EquipmentSlot, Equipped, HungerClock, HungerState, MeleeWeapon, Name, NaturalAttackDefense, Pools, Position, Skill, Skills, SufferDamage, WantsToMelee, Wearable};
+use crate::components::{Attributes, Energy, EquipmentSlot, Equipped, HungerClock, HungerState, MeleeWeapon, Name, NaturalAttack, NaturalAttackDefense, Overloaded, Pools, Position, RangedWeapon, Shield, Skill, Skills, SufferDamage, WantsToMelee, Wearable};
 use crate::components::WeaponAttribute::Might;
-use crate::gamelog::GameLog;
-use crate::gamesystem::skill_bonus;
+use crate::encumbrance_system::OVERLOADED_COMBAT_PENALTY;
+use crate::energy_system::MELEE_COST;
+use crate::gamelog::{GameLog, LogBuilder, LogCategory};
+use crate::gamesystem::{block_chance, block_reduction, skill_bonus};
 use crate::particle_system::ParticleBuilder;
+use crate::sound_system::{Sounds, FIGHT_NOISE};
 
 pub struct MeleeCombatSystem {}
 
@@ -28,7 +31,13 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, Equipped>,
         ReadStorage<'a, MeleeWeapon>,
         ReadStorage<'a, Wearable>,
-        ReadStorage<'a, NaturalAttackDefense>
+        ReadStorage<'a, NaturalAttackDefense>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, Energy>,
+        ReadStorage<'a, Overloaded>,
+        ReadStorage<'a, Shield>,
+        WriteExpect<'a, Sounds>,
+        ReadStorage<'a, RangedWeapon>
     );
     fn run(&mut self, data: Self::SystemData) {
         let (
@@ -47,33 +56,30 @@ impl<'a> System<'a> for MeleeCombatSystem {
             equipped_items,
             melee_weapons,
             wearables,
-            natural
+            natural,
+            player_entity,
+            mut energy,
+            overloaded,
+            shields,
+            mut sounds,
+            ranged_weapons
         ) = data;
 
 
 
         for (entity, wants_melee, name, attacker_attrs, attacker_skills, attacker_pools) in (&entities, &wants_melee, &names, &attributes, &skills, &pools).join() {
-            let mut weapon = MeleeWeapon {
-                attribute: Might,
-                hit_bonus: 0,
-                damage_n_dice: 1,
-                damage_die_type: 4,
-                damage_bonus: 0
-            };
+            Energy::spend(&mut energy, entity, MELEE_COST);
+            if let Some(pos) = positions.get(entity) {
+                sounds.emit(entity, pos.x, pos.y, FIGHT_NOISE);
+            }
+            let mut weapon = unarmed_weapon();
             if let Some(nat) = natural.get(entity) {
-                if !nat.attacks.is_empty() {
-                    let attack_idx = if nat.attacks.len() == 1 {
-                        0
-                    } else {
-                        rng.roll_dice(1, nat.attacks.len() as i32) as usize -1
-                    };
-                    weapon.hit_bonus = nat.attacks[attack_idx].hit_bonus;
-                    weapon.damage_n_dice = nat.attacks[attack_idx].damage_n_dice;
-                    weapon.damage_die_type = nat.attacks[attack_idx].damage_die_type;
-                    weapon.damage_bonus = nat.attacks[attack_idx].damage_bonus;
+                if let Some(natural_weapon) = roll_natural_attack(&mut rng, nat) {
+                    weapon = natural_weapon;
                 }
             }
-            for(wielded, melee) in (&equipped_items, &melee_weapons).join() {
+            // Bows and the like are no use up close, so whoever holds one fights as if unarmed
+            for(wielded, melee, _) in (&equipped_items, &melee_weapons, !&ranged_weapons).join() {
                 if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
                     weapon = melee.clone();
                 }
@@ -84,70 +90,71 @@ impl<'a> System<'a> for MeleeCombatSystem {
             if attacker_pools.hit_points.current > 0 && target_pools.hit_points.current > 0 {
                 let target_name = names.get(wants_melee.target).unwrap();
 
-                let natural_roll = rng.roll_dice(1, 20);
-                let attribute_hit_bonus = attacker_attrs.might.bonus;
-                let skill_hit_bonus = skill_bonus(Skill::Melee, &*attacker_skills);
-                let weapon_hit_bonus = weapon.hit_bonus;
-                let mut status_hit_bonus = 0;
-                if let Some(hc) = hunger_clock.get(entity) {
-                    if hc.state == HungerState::WellFed {
-                        status_hit_bonus += 1;
-                    }
-                }
-                let modified_hit_roll = natural_roll +
-                    attribute_hit_bonus +
-                    skill_hit_bonus +
-                    weapon_hit_bonus +
-                    status_hit_bonus;
-
-                let base_armor_class = natural.get(wants_melee.target).map_or(
-                    10,
-                    |n|n.armor_class.unwrap_or(10)
-                );
-                let armor_quickness_bonus =  target_attrs.quickness.bonus;
-                let armor_skill_bonus = skill_bonus(Skill::Defense, &*target_skills);
+                let well_fed = hunger_clock.get(entity).is_some_and(|hc| hc.state == HungerState::WellFed);
+                let (natural_roll, modified_hit_roll) = hit_roll(&mut rng, attacker_attrs, attacker_skills, &weapon, well_fed, overloaded.get(entity).is_some());
+
                 let mut armor_item_bonus = 0.0;
                 for (wielded, armor) in (&equipped_items, &wearables).join() {
                    if wielded.owner == wants_melee.target {
                        armor_item_bonus += armor.armor_class;
                    }
                 }
-                let armor_class = base_armor_class +
-                    armor_quickness_bonus +
-                    armor_skill_bonus +
-                    armor_item_bonus as i32;
-
-                // Target is hit if not crit fail AND either natural 20 or modified role is greater
-                // than armor class
-                if natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class) {
-                    let base_damage = rng.roll_dice(
-                        weapon.damage_n_dice,
-                        weapon.damage_die_type
-                    );
-                    let attr_damage_bonus = attacker_attrs.might.bonus;
-                    let skill_damage_bonus = skill_hit_bonus; // save computation from above
-                    let weapon_damage_bonus =  weapon.hit_bonus;
-
-                    let damage = i32::max(
-                        0,
-                        base_damage +
-                            attr_damage_bonus +
-                            skill_hit_bonus +
-                            skill_damage_bonus +
-                            weapon_damage_bonus
-                    );
-                    SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
-                    gamelog.entries.push(
-                        format!("{} hits {} for {} hp.", &name.name, &target_name.name, damage)
-                    );
+                let armor_class = armor_class(
+                    natural.get(wants_melee.target),
+                    target_attrs,
+                    target_skills,
+                    armor_item_bonus,
+                    overloaded.get(wants_melee.target).is_some()
+                );
+
+                if attack_hits(natural_roll, modified_hit_roll, armor_class) {
+                    let mut damage = damage_roll(&mut rng, attacker_attrs, attacker_skills, &weapon);
+                    let mut blocked = 0;
+                    for (wielded, shield) in (&equipped_items, &shields).join() {
+                        if wielded.owner == wants_melee.target && wielded.slot == EquipmentSlot::Shield {
+                            if let Some(reduction) = shield_block(&mut rng, shield, target_skills) {
+                                blocked = i32::min(damage, reduction);
+                            }
+                        }
+                    }
+                    damage -= blocked;
+                    if blocked > 0 && damage == 0 {
+                        let message = if wants_melee.target == *player_entity {
+                            format!("You block the {}'s attack.", &name.name)
+                        } else if entity == *player_entity {
+                            format!("The {} blocks your attack.", &target_name.name)
+                        } else {
+                            format!("{} blocks {}'s attack.", &target_name.name, &name.name)
+                        };
+                        gamelog.add(LogCategory::Combat, message);
+                    } else {
+                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage, entity == *player_entity, Some(entity));
+                        let hit = LogBuilder::new(LogCategory::Combat)
+                            .name(&name.name)
+                            .append(" hits ")
+                            .name(&target_name.name)
+                            .append(" for ")
+                            .damage(damage);
+                        if blocked > 0 {
+                            hit.append(format!(" hp ({} blocked).", blocked)).log(&mut gamelog);
+                        } else {
+                            hit.append(" hp.").log(&mut gamelog);
+                        }
+                    }
                 } else if natural_roll == 1 {
-                    gamelog.entries.push(
-                        format!("{} tries to hit {}, but misses.", &name.name, &target_name.name)
-                    );
+                    LogBuilder::new(LogCategory::Combat)
+                        .name(&name.name)
+                        .append(" tries to hit ")
+                        .name(&target_name.name)
+                        .append(", but misses.")
+                        .log(&mut gamelog);
                 } else {
-                    gamelog.entries.push(
-                        format!("{} hits {}, but it does no damage.", &name.name, &target_name.name)
-                    );
+                    LogBuilder::new(LogCategory::Combat)
+                        .name(&name.name)
+                        .append(" hits ")
+                        .name(&target_name.name)
+                        .append(", but it does no damage.")
+                        .log(&mut gamelog);
                 }
                 if let Some(pos) = positions.get(wants_melee.target) {
                     particle_builder.request(pos.x, pos.y, RGB::named(ORANGE), RGB::named(BLACK), to_cp437('!'), 200.0);
@@ -157,3 +164,132 @@ impl<'a> System<'a> for MeleeCombatSystem {
         wants_melee.clear();
     }
 }
+
+pub fn unarmed_weapon() -> MeleeWeapon {
+    MeleeWeapon {
+        attribute: Might,
+        hit_bonus: 0,
+        damage_n_dice: 1,
+        damage_die_type: 4,
+        damage_bonus: 0
+    }
+}
+
+pub fn roll_natural_attack(rng: &mut RandomNumberGenerator, nat: &NaturalAttackDefense) -> Option<MeleeWeapon> {
+    let attacks: Vec<&NaturalAttack> = nat.attacks.iter().filter(|a| a.range.is_none()).collect();
+    if attacks.is_empty() {
+        return None;
+    }
+    let attack_idx = if attacks.len() == 1 {
+        0
+    } else {
+        rng.roll_dice(1, attacks.len() as i32) as usize -1
+    };
+    Some(natural_weapon(attacks[attack_idx]))
+}
+
+/// The longest reaching of a creature's natural ranged attacks, such as spitting or throwing spines
+pub fn natural_ranged_attack(nat: &NaturalAttackDefense) -> Option<(MeleeWeapon, i32)> {
+    nat.attacks
+        .iter()
+        .filter_map(|a| a.range.map(|range| (a, range)))
+        .max_by_key(|(_, range)| *range)
+        .map(|(a, range)| (natural_weapon(a), range))
+}
+
+fn natural_weapon(attack: &NaturalAttack) -> MeleeWeapon {
+    let mut weapon = unarmed_weapon();
+    weapon.hit_bonus = attack.hit_bonus;
+    weapon.damage_n_dice = attack.damage_n_dice;
+    weapon.damage_die_type = attack.damage_die_type;
+    weapon.damage_bonus = attack.damage_bonus;
+    weapon
+}
+
+/// Returns the natural d20 roll and the roll with all of the attacker's bonuses applied
+pub fn hit_roll(rng: &mut RandomNumberGenerator, attacker_attrs: &Attributes, attacker_skills: &Skills, weapon: &MeleeWeapon, well_fed: bool, overloaded: bool) -> (i32, i32) {
+    let natural_roll = rng.roll_dice(1, 20);
+    let modified_hit_roll = natural_roll +
+        hit_bonus_sources(attacker_attrs, attacker_skills, weapon, well_fed, overloaded)
+            .iter()
+            .map(|(_, bonus)| bonus)
+            .sum::<i32>();
+    (natural_roll, modified_hit_roll)
+}
+
+/// Everything added to the attacker's d20, labelled by where it comes from
+pub fn hit_bonus_sources(attacker_attrs: &Attributes, attacker_skills: &Skills, weapon: &MeleeWeapon, well_fed: bool, overloaded: bool) -> Vec<(&'static str, i32)> {
+    let mut sources = vec![
+        ("Might", attacker_attrs.might.bonus),
+        ("Melee skill", skill_bonus(Skill::Melee, attacker_skills)),
+        ("Weapon", weapon.hit_bonus),
+    ];
+    if well_fed {
+        sources.push(("Well fed", 1));
+    }
+    if overloaded {
+        sources.push(("Overloaded", -OVERLOADED_COMBAT_PENALTY));
+    }
+    sources
+}
+
+pub fn armor_class(natural: Option<&NaturalAttackDefense>, target_attrs: &Attributes, target_skills: &Skills, armor_item_bonus: f32, overloaded: bool) -> i32 {
+    armor_class_sources(natural, target_attrs, target_skills, armor_item_bonus, overloaded)
+        .iter()
+        .map(|(_, bonus)| bonus)
+        .sum()
+}
+
+/// The parts that make up an armour class, labelled by where they come from
+pub fn armor_class_sources(natural: Option<&NaturalAttackDefense>, target_attrs: &Attributes, target_skills: &Skills, armor_item_bonus: f32, overloaded: bool) -> Vec<(&'static str, i32)> {
+    let base_armor_class = natural.map_or(
+        10,
+        |n|n.armor_class.unwrap_or(10)
+    );
+    let mut sources = vec![
+        ("Base", base_armor_class),
+        ("Quickness", target_attrs.quickness.bonus),
+        ("Defense skill", skill_bonus(Skill::Defense, target_skills)),
+        ("Armour", armor_item_bonus as i32),
+    ];
+    if overloaded {
+        sources.push(("Overloaded", -OVERLOADED_COMBAT_PENALTY));
+    }
+    sources
+}
+
+// Target is hit if not crit fail AND either natural 20 or modified role is greater
+// than armor class
+pub fn attack_hits(natural_roll: i32, modified_hit_roll: i32, armor_class: i32) -> bool {
+    natural_roll != 1 && (natural_roll == 20 || modified_hit_roll > armor_class)
+}
+
+pub fn damage_roll(rng: &mut RandomNumberGenerator, attacker_attrs: &Attributes, attacker_skills: &Skills, weapon: &MeleeWeapon) -> i32 {
+    let base_damage = rng.roll_dice(
+        weapon.damage_n_dice,
+        weapon.damage_die_type
+    );
+    let attr_damage_bonus = attacker_attrs.might.bonus;
+    let skill_hit_bonus = skill_bonus(Skill::Melee, attacker_skills);
+    let skill_damage_bonus = skill_hit_bonus;
+    let weapon_damage_bonus =  weapon.hit_bonus;
+
+    i32::max(
+        0,
+        base_damage +
+            attr_damage_bonus +
+            skill_hit_bonus +
+            skill_damage_bonus +
+            weapon_damage_bonus
+    )
+}
+
+/// Returns how much damage the shield soaks up if the block succeeds
+pub fn shield_block(rng: &mut RandomNumberGenerator, shield: &Shield, target_skills: &Skills) -> Option<i32> {
+    let defense = skill_bonus(Skill::Defense, target_skills);
+    if rng.roll_dice(1, 100) <= block_chance(shield.block_chance, defense) {
+        Some(block_reduction(shield.damage_reduction, defense))
+    } else {
+        None
+    }
+}
@@ -1,61 +1,162 @@
+use std::collections::{HashMap, HashSet};
+
 use bracket_lib::color::BLACK;
-use bracket_lib::prelude::{a_star_search, console, to_cp437, DistanceAlg, Point, MAGENTA, RGB};
+use bracket_lib::prelude::{
+    a_star_search, console, to_cp437, DijkstraMap, DistanceAlg, Point, MAGENTA, RGB,
+};
+use bracket_lib::random::RandomNumberGenerator;
 use specs::prelude::*;
 
-use crate::components::{Confusion, EntityMoved, Monster, Position, Viewshed, WantsToMelee};
+use crate::components::{
+    Asleep, Behaviour, BehaviourAction, BehaviourCondition, Confusion, Energy, EntityMoved,
+    Equipped, Faction, Home, InflictsDamage, Item, KnownSpell, Locked, MeleeWeapon, Memory, MyTurn,
+    NaturalAttackDefense, OpensDoors, PackMember, Pools, Position, ProvidesHealing, Ranged,
+    RangedWeapon, Reaction, Reputation, Schedule, SchedulePlace, Viewshed, WantsToCastSpell,
+    WantsToMelee, WantsToPickUpItem, WantsToShoot,
+};
+use crate::doors::{door_at, set_door, DoorStorages};
+use crate::energy_system::{MOVE_COST, WAIT_COST};
+use crate::flow_map_system::{FlowMaps, TerrainView};
+use crate::gameclock::GameClock;
+use crate::map::tiletype::tile_walkable;
 use crate::map::Map;
+use crate::rect::Rect;
 use crate::particle_system::ParticleBuilder;
-use crate::{RunState, DEBUGGING};
+use crate::ranged_combat_system::ranged_weapon;
+use crate::raws::rawmaster::{faction_reaction, RawMaster};
+use crate::raws::RAWS;
+use crate::DEBUGGING;
+
+/// How many turns a monster keeps looking around after reaching the place it last saw its target
+pub const SEARCH_TURNS: i32 = 8;
+/// Looters ignore items that are further away than this
+const LOOT_RANGE: f32 = 12.0;
+
+/// Once the player's standing with a faction drops this low, its members attack them on sight
+pub const HOSTILE_STANDING: i32 = -20;
 
 pub struct MonsterAI {}
 
+// Lets a monster that can open doors route through them; the set holds the tiles it still can't pass
+type DoorAccess<'s, 'a> = Option<(&'s HashSet<usize>, &'s mut DoorStorages<'a>)>;
+
+enum Outcome {
+    Moved,
+    Attacked,
+    PickedUp,
+    Cast,
+    OpenedDoor,
+    Waited,
+}
+
 impl<'a> System<'a> for MonsterAI {
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadExpect<'a, Point>,
-        ReadExpect<'a, Entity>,
-        ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
-        ReadStorage<'a, Monster>,
+        ReadStorage<'a, Behaviour>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
         WriteStorage<'a, Confusion>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        ReadStorage<'a, MyTurn>,
+        WriteStorage<'a, Energy>,
+        ReadStorage<'a, Pools>,
+        ReadStorage<'a, Home>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Memory>,
+        ReadStorage<'a, Asleep>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, Reputation>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, FlowMaps>,
+        ReadStorage<'a, Item>,
+        WriteStorage<'a, WantsToPickUpItem>,
+        ReadStorage<'a, PackMember>,
+        (
+            WriteStorage<'a, WantsToShoot>,
+            ReadStorage<'a, Equipped>,
+            ReadStorage<'a, MeleeWeapon>,
+            ReadStorage<'a, RangedWeapon>,
+            ReadStorage<'a, NaturalAttackDefense>,
+        ),
+        (
+            WriteStorage<'a, WantsToCastSpell>,
+            ReadStorage<'a, KnownSpell>,
+            ReadStorage<'a, Ranged>,
+            ReadStorage<'a, ProvidesHealing>,
+            ReadStorage<'a, InflictsDamage>,
+        ),
+        (
+            ReadStorage<'a, Schedule>,
+            ReadExpect<'a, GameClock>,
+            DoorStorages<'a>,
+            ReadStorage<'a, Locked>,
+            ReadStorage<'a, OpensDoors>,
+        ),
     );
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
-            player_pos,
-            player_entity,
-            runstate,
             entities,
             mut viewshed,
-            monster,
+            behaviours,
             mut position,
             mut wants_to_melee,
             mut confusion,
             mut particle_builder,
             mut entity_moved,
+            turns,
+            mut energy,
+            pools,
+            homes,
+            mut rng,
+            mut memories,
+            asleep,
+            factions,
+            reputations,
+            player_entity,
+            flow_maps,
+            items,
+            mut wants_pickup,
+            pack_members,
+            (mut wants_shoot, equipped, melee_weapons, ranged_weapons, natural),
+            (mut wants_cast, known_spells, ranged, healing, inflicts_damage),
+            (schedules, clock, mut door_storages, locked, opens_doors),
         ) = data;
-
-        if *runstate != RunState::MonsterTurn {
-            return;
+        let raws = RAWS.lock().unwrap();
+        let mut packs: HashMap<u32, Vec<Entity>> = HashMap::new();
+        let mut led_packs: HashSet<u32> = HashSet::new();
+        for (member, pack) in (&entities, &pack_members).join() {
+            packs.entry(pack.pack).or_default().push(member);
+            if pack.leader {
+                led_packs.insert(pack.pack);
+            }
         }
+        // Tiles around a target that a pack member is already heading for
+        let mut claimed: HashSet<Point> = HashSet::new();
+        // Props that stand in the way even of those who can open doors
+        let impassable: HashSet<usize> = (&entities, &position, &door_storages.1, !&pools)
+            .join()
+            .filter(|(e, ..)| door_storages.0.get(*e).is_none() || locked.get(*e).is_some())
+            .map(|(_, p, ..)| map.xy_idx(p.x, p.y))
+            .collect();
 
-        for (entity, viewshed, _monster, pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        for (entity, viewshed, behaviour, pos, _turn) in
+            (&entities, &mut viewshed, &behaviours, &mut position, &turns).join()
         {
-            let mut can_act = true;
-
+            if asleep.get(entity).is_some() {
+                Energy::spend(&mut energy, entity, WAIT_COST);
+                continue;
+            }
             let is_confused = confusion.get_mut(entity);
             if let Some(i_am_confused) = is_confused {
                 i_am_confused.turns -= 1;
                 if i_am_confused.turns < 1 {
                     confusion.remove(entity);
                 }
-                can_act = false;
+                Energy::spend(&mut energy, entity, WAIT_COST);
                 particle_builder.request(
                     pos.x,
                     pos.y,
@@ -64,61 +165,618 @@ impl<'a> System<'a> for MonsterAI {
                     to_cp437('?'),
                     200.0,
                 );
+                continue;
             }
-            if can_act {
-                let distance =
-                    DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
-                if distance < 1.5 {
-                    wants_to_melee
-                        .insert(
-                            entity,
-                            WantsToMelee {
-                                target: *player_entity,
-                            },
-                        )
-                        .expect("Failed to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) {
-                    let path = a_star_search(
-                        map.xy_idx(pos.x, pos.y) as i32,
-                        map.xy_idx(player_pos.x, player_pos.y) as i32,
-                        &mut *map,
-                    );
-                    if path.success && path.steps.len() > 1 {
-                        let (new_x, new_y) = (
-                            path.steps[1] as i32 % map.width,
-                            path.steps[1] as i32 / map.width,
-                        );
-                        if DEBUGGING {
-                            let dir = match (new_x - pos.x, new_y - pos.y) {
-                                (-1, 0) => "west",
-                                (-1, -1) => "northwest",
-                                (0, -1) => "north",
-                                (1, -1) => "northeast",
-                                (1, 0) => "east",
-                                (1, 1) => "southeast",
-                                (0, 1) => "south",
-                                (-1, 1) => "southwest",
-                                _ => "wat",
-                            };
-                            console::log(format!("step: ({},{}), dir={}", new_x, new_y, dir));
-                            let coord_path: Vec<_> = path
-                                .steps
-                                .iter()
-                                .map(|v| (v % map.width as usize, v / map.width as usize))
-                                .collect();
-                            console::log(format!("the path: {:?}", coord_path));
+
+            let my_pos = Point::new(pos.x, pos.y);
+            let my_faction = factions.get(entity).map_or("Monsters", |f| f.name.as_str());
+            let mut target: Option<(Entity, Point, f32)> = None;
+            let mut threat: Option<(Entity, Point, f32)> = None;
+            for tile in viewshed.visible_tiles.iter() {
+                let distance = DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                for other in map.tile_content[tile.x as usize][tile.y as usize].iter() {
+                    if *other == entity || pools.get(*other).is_none() {
+                        continue;
+                    }
+                    let their_faction = match factions.get(*other) {
+                        None => continue,
+                        Some(faction) => faction,
+                    };
+                    match reaction_to(&raws, my_faction, &their_faction.name, reputations.get(*other)) {
+                        Reaction::Attack if target.is_none_or(|(_, _, d)| distance < d) => {
+                            target = Some((*other, *tile, distance))
                         }
+                        Reaction::Flee if threat.is_none_or(|(_, _, d)| distance < d) => {
+                            threat = Some((*other, *tile, distance))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            let home = homes.get(entity).map(|h| Point::new(h.x, h.y));
+            let opener = opens_doors.get(entity).is_some();
+            let pack = pack_members.get(entity);
+            if let Some((_, target_pos, _)) = target {
+                // Whatever one member of a pack sees, the rest of the pack knows about
+                let knows = pack.and_then(|p| packs.get(&p.pack)).cloned().unwrap_or_else(|| vec![entity]);
+                for mate in knows.iter() {
+                    if let Some(memory) = memories.get_mut(*mate) {
+                        memory.last_seen = Some(target_pos);
+                        memory.search_turns = SEARCH_TURNS;
+                    }
+                }
+            }
+            let leaderless = pack.is_some_and(|p| !led_packs.contains(&p.pack));
 
-                        pos.x = new_x;
-                        pos.y = new_y;
-                        map.blocked[pos.x as usize][pos.y as usize] = true;
-                        viewshed.dirty = true;
-                        entity_moved
-                            .insert(entity, EntityMoved {})
-                            .expect("Unable to insert marker");
+            // Getting away from anything we're afraid of comes before anything else we might want to do,
+            // and a pack that has lost its leader loses its nerve as well
+            let mut outcome = match (threat, target) {
+                (Some((threat, threat_pos, _)), _) => {
+                    escape(&mut map, pos, &flow_maps, *player_entity, threat, threat_pos)
+                }
+                (None, Some((target, target_pos, _))) if leaderless => {
+                    escape(&mut map, pos, &flow_maps, *player_entity, target, target_pos)
+                }
+                _ => None,
+            };
+            for rule in behaviour.rules.iter() {
+                if outcome.is_some() {
+                    break;
+                }
+                let condition_met = match rule.condition {
+                    BehaviourCondition::Always => true,
+                    BehaviourCondition::SeesEnemy => target.is_some(),
+                    BehaviourCondition::EnemyWithin(range) => {
+                        target.is_some_and(|(_, _, distance)| distance <= range as f32)
                     }
+                    BehaviourCondition::HpBelow(percent) => pools.get(entity).is_some_and(|p| {
+                        p.hit_points.current * 100 < p.hit_points.max * percent
+                    }),
+                    BehaviourCondition::AwayFromHome(range) => home.is_some_and(|h| {
+                        DistanceAlg::Pythagoras.distance2d(my_pos, h) > range as f32
+                    }),
+                };
+                if !condition_met {
+                    continue;
                 }
+
+                outcome = match (rule.action, target) {
+                    (BehaviourAction::Attack, Some((target, _, distance))) if distance < 1.5 => {
+                        wants_to_melee
+                            .insert(entity, WantsToMelee { target })
+                            .expect("Failed to insert attack");
+                        Some(Outcome::Attacked)
+                    }
+                    (BehaviourAction::Attack, _) => None,
+                    (BehaviourAction::Shoot, Some((_, target_pos, distance))) => {
+                        match ranged_weapon(entity, &equipped, &melee_weapons, &ranged_weapons, &natural) {
+                            Some((_, range)) if distance <= range as f32 => {
+                                wants_shoot
+                                    .insert(entity, WantsToShoot { target: target_pos })
+                                    .expect("Failed to insert shot");
+                                Some(Outcome::Attacked)
+                            }
+                            _ => None,
+                        }
+                    }
+                    (BehaviourAction::Shoot, None) => None,
+                    (BehaviourAction::Cast, _) => {
+                        let my_pools = pools.get(entity);
+                        let hurt = my_pools.is_some_and(|p| p.hit_points.current * 2 < p.hit_points.max);
+                        let mana = my_pools.map_or(0, |p| p.mana.current);
+                        let mut choice = None;
+                        for (spell_entity, spell) in (&entities, &known_spells).join() {
+                            if spell.owner != entity || spell.mana_cost > mana {
+                                continue;
+                            }
+                            let range = ranged.get(spell_entity).map(|r| r.range);
+                            let offensive = inflicts_damage.get(spell_entity).is_some()
+                                || confusion.get(spell_entity).is_some();
+                            if hurt && range.is_none() && healing.get(spell_entity).is_some() {
+                                choice = Some((spell_entity, None));
+                                break;
+                            }
+                            if let (Some(range), true, Some((_, target_pos, distance))) =
+                                (range, offensive, target)
+                            {
+                                if distance <= range as f32 && choice.is_none() {
+                                    choice = Some((spell_entity, Some(target_pos)));
+                                }
+                            }
+                        }
+                        choice.map(|(spell, spell_target)| {
+                            wants_cast
+                                .insert(entity, WantsToCastSpell { spell, target: spell_target })
+                                .expect("Failed to insert spell");
+                            Outcome::Cast
+                        })
+                    }
+                    (BehaviourAction::Chase, Some((target, target_pos, distance))) => {
+                        let follower = pack.is_some_and(|p| !p.leader);
+                        let closing_in = if follower {
+                            close_in(
+                                &mut map,
+                                pos,
+                                &mut claimed,
+                                target_pos,
+                                distance,
+                                door_access(opener, &impassable, &mut door_storages),
+                            )
+                        } else {
+                            None
+                        };
+                        closing_in.or_else(|| {
+                            Some(pursue(
+                                &mut map,
+                                pos,
+                                &flow_maps,
+                                *player_entity,
+                                target,
+                                target_pos,
+                                door_access(opener, &impassable, &mut door_storages),
+                            ))
+                        })
+                    }
+                    (BehaviourAction::Chase, None) => {
+                        let doors = door_access(opener, &impassable, &mut door_storages);
+                        search(&mut map, pos, memories.get_mut(entity), &mut rng, doors)
+                    }
+                    (BehaviourAction::Flee, _) => {
+                        match threat.or(target) {
+                            Some((danger, danger_pos, _)) => {
+                                escape(&mut map, pos, &flow_maps, *player_entity, danger, danger_pos)
+                            }
+                            None => None,
+                        }
+                    }
+                    (BehaviourAction::KeepDistance(range), Some((target, target_pos, distance))) => {
+                        if distance < range as f32 {
+                            escape(&mut map, pos, &flow_maps, *player_entity, target, target_pos)
+                        } else {
+                            None
+                        }
+                    }
+                    (BehaviourAction::KeepDistance(_), None) => None,
+                    (BehaviourAction::GuardArea(range), Some((target, target_pos, _))) => match home {
+                        Some(home)
+                            if DistanceAlg::Pythagoras.distance2d(home, target_pos)
+                                <= range as f32 =>
+                        {
+                            Some(pursue(
+                                &mut map,
+                                pos,
+                                &flow_maps,
+                                *player_entity,
+                                target,
+                                target_pos,
+                                door_access(opener, &impassable, &mut door_storages),
+                            ))
+                        }
+                        _ => None,
+                    },
+                    (BehaviourAction::GuardArea(_), None) => None,
+                    (BehaviourAction::ReturnHome, _) => match home {
+                        Some(home) if home != my_pos => {
+                            let doors = door_access(opener, &impassable, &mut door_storages);
+                            Some(step_towards(&mut map, pos, home, doors))
+                        }
+                        _ => None,
+                    },
+                    (BehaviourAction::Loot, _) => {
+                        loot(&mut map, pos, entity, &flow_maps, &items, &mut wants_pickup)
+                    }
+                    (BehaviourAction::FollowSchedule, _) => {
+                        let place = schedules.get(entity).and_then(|s| s.place_at(clock.hour()));
+                        match place.and_then(|place| schedule_goal(&map, my_pos, place, home, &impassable)) {
+                            None => None,
+                            Some(goal) => {
+                                let doors = door_access(opener, &impassable, &mut door_storages);
+                                Some(step_towards(&mut map, pos, goal, doors))
+                            }
+                        }
+                    }
+                    (BehaviourAction::Wander, _) => Some(wander(&mut map, pos, &mut rng)),
+                    (BehaviourAction::Idle, _) => Some(Outcome::Waited),
+                };
+            }
+
+            match outcome {
+                Some(Outcome::Moved) => {
+                    viewshed.dirty = true;
+                    entity_moved
+                        .insert(entity, EntityMoved {})
+                        .expect("Unable to insert marker");
+                    Energy::spend(&mut energy, entity, MOVE_COST);
+                }
+                Some(Outcome::OpenedDoor) => {
+                    viewshed.dirty = true;
+                    Energy::spend(&mut energy, entity, MOVE_COST);
+                }
+                // Attacks, spells and pickups are paid for when they resolve
+                Some(Outcome::Attacked) | Some(Outcome::PickedUp) | Some(Outcome::Cast) => {}
+                Some(Outcome::Waited) | None => Energy::spend(&mut energy, entity, WAIT_COST),
+            }
+        }
+    }
+}
+
+pub fn reaction_to(
+    raws: &RawMaster,
+    my_faction: &str,
+    their_faction: &str,
+    reputation: Option<&Reputation>,
+) -> Reaction {
+    if let Some(standing) = reputation.and_then(|r| r.standing.get(my_faction)) {
+        if *standing <= HOSTILE_STANDING {
+            return Reaction::Attack;
+        }
+    }
+    faction_reaction(my_faction, their_faction, raws)
+}
+
+// Heads for a target, riding the shared flow map when it's the player rather than pathing from scratch
+fn pursue(
+    map: &mut Map,
+    pos: &mut Position,
+    flow_maps: &FlowMaps,
+    player: Entity,
+    target: Entity,
+    target_pos: Point,
+    doors: DoorAccess,
+) -> Outcome {
+    if target == player {
+        if let Some(outcome) = follow_flow(map, pos, &flow_maps.to_player) {
+            return outcome;
+        }
+        // The flow maps treat closed doors as walls, so only look for another way if we can open them
+        if doors.is_none() {
+            return Outcome::Waited;
+        }
+    }
+    step_towards(map, pos, target_pos, doors)
+}
+
+fn door_access<'s, 'a>(
+    opener: bool,
+    impassable: &'s HashSet<usize>,
+    storages: &'s mut DoorStorages<'a>,
+) -> DoorAccess<'s, 'a> {
+    if opener {
+        Some((impassable, storages))
+    } else {
+        None
+    }
+}
+
+// Pack followers hold back at the mouth of a corridor rather than queue up inside it, and otherwise
+// each head for a different tile around the target so that the pack surrounds it
+fn close_in(
+    map: &mut Map,
+    pos: &mut Position,
+    claimed: &mut HashSet<Point>,
+    target_pos: Point,
+    distance: f32,
+    doors: DoorAccess,
+) -> Option<Outcome> {
+    let my_pos = Point::new(pos.x, pos.y);
+    if distance >= 2.0 && is_corridor(map, target_pos) && !is_corridor(map, my_pos) {
+        return Some(Outcome::Waited);
+    }
+    let mut best: Option<(Point, f32)> = None;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let spot = Point::new(target_pos.x + dx, target_pos.y + dy);
+            if (dx == 0 && dy == 0) || claimed.contains(&spot) || !map.is_tile_in_bounds(spot.x, spot.y) {
+                continue;
+            }
+            if spot != my_pos && map.blocked[spot.x as usize][spot.y as usize] {
+                continue;
+            }
+            let spot_distance = DistanceAlg::Pythagoras.distance2d(my_pos, spot);
+            if best.is_none_or(|(_, d)| spot_distance < d) {
+                best = Some((spot, spot_distance));
             }
         }
     }
+    let (spot, _) = best?;
+    claimed.insert(spot);
+    if spot == my_pos {
+        return Some(Outcome::Waited);
+    }
+    Some(step_towards(map, pos, spot, doors))
+}
+
+fn is_corridor(map: &Map, point: Point) -> bool {
+    let mut open = 0;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let (x, y) = (point.x + dx, point.y + dy);
+            if (dx != 0 || dy != 0) && map.is_tile_in_bounds(x, y) && tile_walkable(map.tiles[x as usize][y as usize]) {
+                open += 1;
+            }
+        }
+    }
+    open <= 2
+}
+
+fn escape(
+    map: &mut Map,
+    pos: &mut Position,
+    flow_maps: &FlowMaps,
+    player: Entity,
+    danger: Entity,
+    danger_pos: Point,
+) -> Option<Outcome> {
+    if danger == player {
+        if let Some(outcome) = follow_flow(map, pos, &flow_maps.from_player) {
+            return Some(outcome);
+        }
+    }
+    step_away(map, pos, danger_pos)
+}
+
+fn loot(
+    map: &mut Map,
+    pos: &mut Position,
+    entity: Entity,
+    flow_maps: &FlowMaps,
+    items: &ReadStorage<Item>,
+    wants_pickup: &mut WriteStorage<WantsToPickUpItem>,
+) -> Option<Outcome> {
+    let item_here = map.tile_content[pos.x as usize][pos.y as usize]
+        .iter()
+        .find(|e| items.get(**e).is_some())
+        .copied();
+    if let Some(item) = item_here {
+        wants_pickup
+            .insert(entity, WantsToPickUpItem { collected_by: entity, item })
+            .expect("Unable to insert want to pickup");
+        return Some(Outcome::PickedUp);
+    }
+    if flow_maps.to_items.map[map.xy_idx(pos.x, pos.y)] > LOOT_RANGE {
+        return None;
+    }
+    follow_flow(map, pos, &flow_maps.to_items)
+}
+
+// Where the schedule wants us to be, or None if we're already there. Landmarks are whole buildings, so
+// aim for the free tile nearest the middle of the closest one.
+fn schedule_goal(
+    map: &Map,
+    my_pos: Point,
+    place: &SchedulePlace,
+    home: Option<Point>,
+    impassable: &HashSet<usize>,
+) -> Option<Point> {
+    let buildings = match place {
+        SchedulePlace::Anywhere => return None,
+        SchedulePlace::Home => {
+            return home.filter(|h| DistanceAlg::Pythagoras.distance2d(my_pos, *h) >= 2.0);
+        }
+        SchedulePlace::Landmark(name) => map.landmarks.get(name)?,
+    };
+    let inside = |building: &Rect, p: Point| {
+        p.x > building.x1 && p.x < building.x2 - 1 && p.y > building.y1 && p.y < building.y2 - 1
+    };
+    if buildings.iter().any(|b| inside(b, my_pos)) {
+        return None;
+    }
+    let building = buildings.iter().min_by(|a, b| {
+        let (ax, ay) = a.center();
+        let (bx, by) = b.center();
+        DistanceAlg::Pythagoras
+            .distance2d(my_pos, Point::new(ax, ay))
+            .partial_cmp(&DistanceAlg::Pythagoras.distance2d(my_pos, Point::new(bx, by)))
+            .unwrap()
+    })?;
+    let (cx, cy) = building.center();
+    let mut best: Option<(Point, f32)> = None;
+    for y in building.y1 + 1..building.y2 - 1 {
+        for x in building.x1 + 1..building.x2 - 1 {
+            if !tile_walkable(map.tiles[x as usize][y as usize]) || impassable.contains(&map.xy_idx(x, y)) {
+                continue;
+            }
+            let distance = DistanceAlg::Pythagoras.distance2d(Point::new(x, y), Point::new(cx, cy));
+            if best.is_none_or(|(_, d)| distance < d) {
+                best = Some((Point::new(x, y), distance));
+            }
+        }
+    }
+    best.map(|(p, _)| p)
+}
+
+// Like step_towards, except that the route may go through closed doors, which get opened on the way
+fn walk_to(
+    map: &mut Map,
+    pos: &mut Position,
+    goal: Point,
+    impassable: &HashSet<usize>,
+    storages: &mut DoorStorages,
+) -> Outcome {
+    let path = a_star_search(
+        map.xy_idx(pos.x, pos.y),
+        map.xy_idx(goal.x, goal.y),
+        &TerrainView::new(map, impassable),
+    );
+    if !path.success || path.steps.len() < 2 {
+        return Outcome::Waited;
+    }
+    let (x, y) = (path.steps[1] as i32 % map.width, path.steps[1] as i32 / map.width);
+    let closed_door = door_at(map, storages, x, y).filter(|d| storages.0.get(*d).is_some_and(|d| !d.open));
+    if let Some(door) = closed_door {
+        set_door(map, storages, door, x, y, true);
+        return Outcome::OpenedDoor;
+    }
+    if map.blocked[x as usize][y as usize] {
+        return Outcome::Waited;
+    }
+    move_to(map, pos, x, y);
+    Outcome::Moved
+}
+
+// Steps to whichever open neighbouring tile has the lowest value, as long as it's an improvement
+fn follow_flow(map: &mut Map, pos: &mut Position, flow: &DijkstraMap) -> Option<Outcome> {
+    let idx = map.xy_idx(pos.x, pos.y);
+    if flow.map.len() <= idx {
+        return None;
+    }
+    let next = DijkstraMap::find_lowest_exit(flow, idx, &*map)?;
+    if flow.map[next] >= flow.map[idx] {
+        return None;
+    }
+    move_to(map, pos, next as i32 % map.width, next as i32 / map.width);
+    Some(Outcome::Moved)
+}
+
+fn move_to(map: &mut Map, pos: &mut Position, x: i32, y: i32) {
+    map.blocked[pos.x as usize][pos.y as usize] = false;
+    pos.x = x;
+    pos.y = y;
+    map.blocked[x as usize][y as usize] = true;
+}
+
+fn step_towards(map: &mut Map, pos: &mut Position, target: Point, doors: DoorAccess) -> Outcome {
+    let path = a_star_search(
+        map.xy_idx(pos.x, pos.y) as i32,
+        map.xy_idx(target.x, target.y) as i32,
+        &*map,
+    );
+    if !path.success || path.steps.len() < 2 {
+        return match doors {
+            Some((impassable, storages)) => walk_to(map, pos, target, impassable, storages),
+            None => Outcome::Waited,
+        };
+    }
+    let (new_x, new_y) = (
+        path.steps[1] as i32 % map.width,
+        path.steps[1] as i32 / map.width,
+    );
+    if DEBUGGING {
+        let dir = match (new_x - pos.x, new_y - pos.y) {
+            (-1, 0) => "west",
+            (-1, -1) => "northwest",
+            (0, -1) => "north",
+            (1, -1) => "northeast",
+            (1, 0) => "east",
+            (1, 1) => "southeast",
+            (0, 1) => "south",
+            (-1, 1) => "southwest",
+            _ => "wat",
+        };
+        console::log(format!("step: ({},{}), dir={}", new_x, new_y, dir));
+        let coord_path: Vec<_> = path
+            .steps
+            .iter()
+            .map(|v| (v % map.width as usize, v / map.width as usize))
+            .collect();
+        console::log(format!("the path: {:?}", coord_path));
+    }
+    if map.blocked[new_x as usize][new_y as usize] {
+        return Outcome::Waited;
+    }
+    move_to(map, pos, new_x, new_y);
+    Outcome::Moved
+}
+
+// Picks the open neighbouring tile that puts the most distance between us and the threat
+fn step_away(map: &mut Map, pos: &mut Position, threat: Point) -> Option<Outcome> {
+    let mut best = None;
+    let mut best_distance = DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), threat);
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            let (x, y) = (pos.x + dx, pos.y + dy);
+            if (dx == 0 && dy == 0) || !map.is_tile_in_bounds(x, y) || map.blocked[x as usize][y as usize] {
+                continue;
+            }
+            let distance = DistanceAlg::Pythagoras.distance2d(Point::new(x, y), threat);
+            if distance > best_distance {
+                best_distance = distance;
+                best = Some((x, y));
+            }
+        }
+    }
+    best.map(|(x, y)| {
+        move_to(map, pos, x, y);
+        Outcome::Moved
+    })
+}
+
+// Heads for the last place the target was seen, then pokes around there until the search runs out
+fn search(
+    map: &mut Map,
+    pos: &mut Position,
+    memory: Option<&mut Memory>,
+    rng: &mut RandomNumberGenerator,
+    doors: DoorAccess,
+) -> Option<Outcome> {
+    let memory = memory?;
+    if let Some(last_seen) = memory.last_seen {
+        if last_seen != Point::new(pos.x, pos.y) {
+            if let Outcome::Moved = step_towards(map, pos, last_seen, doors) {
+                return Some(Outcome::Moved);
+            }
+        }
+        // Either we've arrived or the way is blocked, so start looking around from here
+        memory.last_seen = None;
+    }
+    if memory.search_turns > 0 {
+        memory.search_turns -= 1;
+        return Some(wander(map, pos, rng));
+    }
+    None
+}
+
+fn wander(map: &mut Map, pos: &mut Position, rng: &mut RandomNumberGenerator) -> Outcome {
+    let mut x = pos.x;
+    let mut y = pos.y;
+    match rng.roll_dice(1, 5) {
+        1 => x -= 1,
+        2 => x += 1,
+        3 => y -= 1,
+        4 => y += 1,
+        _ => {}
+    }
+    if (x, y) == (pos.x, pos.y) {
+        return Outcome::Waited;
+    }
+    if x > 0 && x < map.width - 1 && y > 0 && y < map.height - 1 && !map.blocked[x as usize][y as usize] {
+        move_to(map, pos, x, y);
+        return Outcome::Moved;
+    }
+    Outcome::Waited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raws::rawmaster::raws_with_factions;
+
+    fn townsfolk_raws() -> RawMaster {
+        raws_with_factions(&[("Townsfolk", &[("Default", "ignore")])])
+    }
+
+    fn reputation(standing: i32) -> Reputation {
+        Reputation { standing: [("Townsfolk".to_string(), standing)].iter().cloned().collect() }
+    }
+
+    #[test]
+    fn without_a_reputation_the_faction_table_decides() {
+        let raws = townsfolk_raws();
+        assert_eq!(reaction_to(&raws, "Townsfolk", "Player", None), Reaction::Ignore);
+        assert_eq!(reaction_to(&raws, "Townsfolk", "Player", Some(&Reputation::default())), Reaction::Ignore);
+    }
+
+    #[test]
+    fn a_bad_enough_standing_turns_a_faction_hostile() {
+        let raws = townsfolk_raws();
+        assert_eq!(reaction_to(&raws, "Townsfolk", "Player", Some(&reputation(HOSTILE_STANDING + 1))), Reaction::Ignore);
+        assert_eq!(reaction_to(&raws, "Townsfolk", "Player", Some(&reputation(HOSTILE_STANDING))), Reaction::Attack);
+        assert_eq!(reaction_to(&raws, "Townsfolk", "Player", Some(&reputation(-100))), Reaction::Attack);
+    }
+
+    #[test]
+    fn standing_with_one_faction_does_not_affect_another() {
+        let raws = townsfolk_raws();
+        assert_eq!(reaction_to(&raws, "Orcs", "Player", Some(&reputation(-100))), Reaction::Ignore);
+    }
 }
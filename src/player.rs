@@ -1,68 +1,106 @@
 use std::cmp::{max, min};
 use std::usize;
 
-use bracket_lib::prelude::{to_cp437, BTerm, Point, VirtualKeyCode};
+use bracket_lib::prelude::{BTerm, Point, RandomNumberGenerator};
 use specs::prelude::*;
 use specs::{Join, World};
 
+use crate::camera::{get_screen_bounds, VIEWPORT_X, VIEWPORT_Y};
 use crate::components::{
-    BlocksTile, BlocksVisibility, Bystander, Consumable, Door, EntityMoved, HungerClock,
-    HungerState, InBackpack, Item, Monster, Player, Pools, Position, Ranged, Renderable, Vendor,
-    Viewshed, WantsToMelee, WantsToPickUpItem, WantsToUseItem,
+    Attribute, Attributes, BlocksTile, BlocksVisibility, Bystander, Consumable, Dialogue, Door,
+    Energy, EntityMoved, EquipmentChanged, Equipped, Faction, Hidden, InBackpack, Item, Key,
+    KnownSpell, Locked, Name, Player, Pools, Position, Ranged, RangedWeapon, Reaction, Renderable,
+    Reputation, Skill, Skills, Vendor, Viewshed, WantsToCastSpell, WantsToMelee, WantsToPickUpItem,
+    WantsToUseItem, Wearable,
 };
-use crate::gamelog::GameLog;
+use crate::doors::{door_at, doorway_clear, set_door, DoorStorages};
+use crate::energy_system::{MOVE_COST, WAIT_COST};
+use crate::events::{GameEvent, GameEvents};
+use crate::gameclock::GameClock;
+use crate::gamelog::{GameLog, LogCategory};
+use crate::gamesystem::{
+    attr_bonus, mana_at_level, movement_noise, player_hp_at_level, skill_bonus, xp_for_next_level,
+};
+use crate::gui::{LevelUpSelection, VendorMode};
+use crate::keybindings::{Action, BindingContext, KeyBindings};
 use crate::map::tiletype::TileType;
 use crate::map::Map;
+use crate::monster_ai_system::reaction_to;
+use crate::raws::RAWS;
+use crate::sound_system::{Sounds, DOOR_NOISE, LOCKPICK_NOISE};
+use crate::spell_system::player_can_cast;
+use crate::travel::{self, TravelMode};
+use crate::vendor_system::{closed_message, is_open};
 use crate::{RunState, State};
 
+const DIRECTIONS: [(i32, i32); 8] = [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+
 pub fn player_input(gs: &mut State, ctx: &mut BTerm) -> RunState {
-    if ctx.shift && ctx.key.is_some() {
-        let key: Option<i32> = match ctx.key.unwrap() {
-            VirtualKeyCode::Key1 => Some(1),
-            VirtualKeyCode::Key2 => Some(2),
-            VirtualKeyCode::Key3 => Some(3),
-            VirtualKeyCode::Key4 => Some(4),
-            VirtualKeyCode::Key5 => Some(5),
-            VirtualKeyCode::Key6 => Some(6),
-            VirtualKeyCode::Key7 => Some(7),
-            VirtualKeyCode::Key8 => Some(8),
-            VirtualKeyCode::Key9 => Some(9),
-            _ => None,
-        };
-        if let Some(key) = key {
-            return use_consumable_hotkey(gs, key - 1);
+    if ctx.left_click {
+        if let Some(target) = clicked_tile(&gs.ecs, ctx) {
+            return travel::start(&mut gs.ecs, TravelMode::To(target));
         }
     }
-    match ctx.key {
-        None => return RunState::AwaitingInput,
-        Some(key) => match key {
-            VirtualKeyCode::Left | VirtualKeyCode::H => try_move_player(-1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Right | VirtualKeyCode::L => try_move_player(1, 0, &mut gs.ecs),
-
-            VirtualKeyCode::Up | VirtualKeyCode::K => try_move_player(0, -1, &mut gs.ecs),
-
-            VirtualKeyCode::Down | VirtualKeyCode::J => try_move_player(0, 1, &mut gs.ecs),
-
-            VirtualKeyCode::Y => try_move_player(-1, -1, &mut gs.ecs),
-            VirtualKeyCode::U => try_move_player(1, -1, &mut gs.ecs),
-            VirtualKeyCode::N => try_move_player(1, 1, &mut gs.ecs),
-            VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
-            VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
-            VirtualKeyCode::I => return RunState::ShowInventory,
-            VirtualKeyCode::D => return RunState::ShowDropItem,
-            VirtualKeyCode::Escape => return RunState::SaveGame,
-            VirtualKeyCode::Period => {
+    let action = gs.ecs.fetch::<KeyBindings>().action(BindingContext::Game, ctx);
+    match action {
+        None => RunState::AwaitingInput,
+        Some(action) => match action {
+            Action::MoveWest
+            | Action::MoveEast
+            | Action::MoveNorth
+            | Action::MoveSouth
+            | Action::MoveNorthWest
+            | Action::MoveNorthEast
+            | Action::MoveSouthWest
+            | Action::MoveSouthEast => {
+                let (dx, dy) = action.direction().unwrap();
+                try_move_player(dx, dy, &mut gs.ecs)
+            }
+            Action::Wait => skip_turn(&mut gs.ecs),
+            Action::PickUp => get_item(&mut gs.ecs),
+            Action::Inventory => RunState::ShowInventory,
+            Action::Drop => RunState::ShowDropItem,
+            Action::SaveAndQuit => RunState::SaveGame,
+            Action::Descend => {
                 if try_next_level(&mut gs.ecs) {
-                    return RunState::NextLevel;
+                    RunState::NextLevel
+                } else {
+                    RunState::AwaitingInput
                 }
             }
-            VirtualKeyCode::R => return RunState::ShowRemoveItem,
-            _ => return RunState::AwaitingInput,
+            Action::Unequip => RunState::ShowRemoveItem,
+            Action::CastSpell => RunState::ShowCastSpell,
+            Action::Fire => fire_ranged_weapon(&mut gs.ecs),
+            Action::OpenDoor => door_command(&mut gs.ecs, true),
+            Action::CloseDoor => door_command(&mut gs.ecs, false),
+            Action::MessageLog => RunState::ShowLog { scroll: 0, filter: None },
+            Action::CharacterSheet => RunState::CharacterSheet,
+            Action::Look => look_command(&gs.ecs),
+            Action::AutoExplore => travel::start(&mut gs.ecs, TravelMode::Explore),
+            Action::TravelToStairs => travel::start(&mut gs.ecs, TravelMode::Stairs),
+            Action::Help => RunState::ShowHelp,
+            Action::UseConsumable(slot) => use_consumable_hotkey(gs, slot as i32),
+            Action::QuickCast(slot) => cast_spell_hotkey(gs, slot as i32),
+            _ => RunState::AwaitingInput,
         },
     }
-    RunState::PlayerTurn
+}
+
+// The map tile under the mouse, if the player has seen it
+fn clicked_tile(ecs: &World, ctx: &mut BTerm) -> Option<Point> {
+    let (min_x, _max_x, min_y, _max_y) = get_screen_bounds(ecs, ctx);
+    let (mouse_x, mouse_y) = ctx.mouse_pos();
+    let (x, y) = (mouse_x + min_x, mouse_y + min_y);
+    let map = ecs.fetch::<Map>();
+    if mouse_x < VIEWPORT_X
+        && mouse_y < VIEWPORT_Y
+        && map.is_tile_in_bounds(x, y)
+        && map.revealed_tiles[x as usize][y as usize]
+    {
+        Some(Point::new(x, y))
+    } else {
+        None
+    }
 }
 
 fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
@@ -103,43 +141,64 @@ fn use_consumable_hotkey(gs: &mut State, key: i32) -> RunState {
     RunState::PlayerTurn
 }
 
-fn skip_turn(ecs: &mut World) -> RunState {
-    let player_entity = ecs.fetch::<Entity>();
-    let viewshed_components = ecs.read_storage::<Viewshed>();
-    let monsters = ecs.read_storage::<Monster>();
-
-    let map = ecs.fetch::<Map>();
-
-    let mut can_heal = true;
-    let viewshed = viewshed_components.get(*player_entity).unwrap();
-    for tile in viewshed.visible_tiles.iter() {
-        for entity_id in map.tile_content[tile.x as usize][tile.y as usize].iter() {
-            let mob = monsters.get(*entity_id);
-            match mob {
-                None => {}
-                Some(_) => {
-                    can_heal = false;
-                }
-            }
+fn cast_spell_hotkey(gs: &mut State, key: i32) -> RunState {
+    let known_spells = gs.ecs.read_storage::<KnownSpell>();
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let entities = gs.ecs.entities();
+    let mut player_spells = Vec::new();
+    for (entity, spell) in (&entities, &known_spells).join() {
+        if spell.owner == *player_entity {
+            player_spells.push(entity);
         }
     }
 
-    if can_heal {
-        let hunger_clock = ecs.read_storage::<HungerClock>();
-        let hc = hunger_clock.get(*player_entity);
-        if let Some(hc) = hc {
-            match hc.state {
-                HungerState::Hungry | HungerState::Starving => can_heal = false,
-                _ => {}
-            }
+    if (key as usize) < player_spells.len() {
+        if !player_can_cast(&gs.ecs, player_spells[key as usize]) {
+            return RunState::AwaitingInput;
+        }
+        if let Some(ranged) = gs
+            .ecs
+            .read_storage::<Ranged>()
+            .get(player_spells[key as usize])
+        {
+            return RunState::ShowTargeting {
+                range: ranged.range,
+                item: player_spells[key as usize],
+            };
         }
+        let mut intent = gs.ecs.write_storage::<WantsToCastSpell>();
+        intent
+            .insert(
+                *player_entity,
+                WantsToCastSpell {
+                    spell: player_spells[key as usize],
+                    target: None,
+                },
+            )
+            .expect("Unable to insert intent");
+        return RunState::PlayerTurn;
     }
+    RunState::AwaitingInput
+}
 
-    if can_heal {
-        let mut health_components = ecs.write_storage::<Pools>();
-        let pools = health_components.get_mut(*player_entity).unwrap();
-        pools.hit_points.current = i32::min(pools.hit_points.current + 1, pools.hit_points.max);
+fn fire_ranged_weapon(ecs: &mut World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    for (item, wielded, weapon) in (&entities, &equipped, &ranged_weapons).join() {
+        if wielded.owner == player_entity {
+            return RunState::ShowTargeting { range: weapon.range, item };
+        }
     }
+    ecs.write_resource::<GameLog>().add(LogCategory::System, "You don't have a ranged weapon equipped.");
+    RunState::AwaitingInput
+}
+
+fn skip_turn(ecs: &mut World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut energy = ecs.write_storage::<Energy>();
+    Energy::spend(&mut energy, *player_entity, WAIT_COST);
 
     RunState::PlayerTurn
 }
@@ -151,14 +210,19 @@ fn try_next_level(ecs: &mut World) -> bool {
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
-        gamelog
-            .entries
-            .push("There is no way down from here".to_string());
+        gamelog.add(LogCategory::System, "There is no way down from here");
         false
     }
 }
 
-pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
+    let destination = {
+        let ppos = ecs.fetch::<Point>();
+        Point::new(ppos.x + delta_x, ppos.y + delta_y)
+    };
+    if let Some(outcome) = operate_door(ecs, destination.x, destination.y, true) {
+        return outcome;
+    }
     let mut positions = ecs.write_storage::<Position>();
     let players = ecs.write_storage::<Player>();
     let mut viewseheds = ecs.write_storage::<Viewshed>();
@@ -166,32 +230,67 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
     let mut entity_moved = ecs.write_storage::<EntityMoved>();
-    let mut doors = ecs.write_storage::<Door>();
-    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
-    let mut blocks_movement = ecs.write_storage::<BlocksTile>();
-    let mut renderables = ecs.write_storage::<Renderable>();
     let bystanders = ecs.read_storage::<Bystander>();
     let vendors = ecs.read_storage::<Vendor>();
+    let dialogues = ecs.read_storage::<Dialogue>();
+    let names = ecs.read_storage::<Name>();
+    let mut energy = ecs.write_storage::<Energy>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let wearables = ecs.read_storage::<Wearable>();
+    let items = ecs.read_storage::<Item>();
+    let skills = ecs.read_storage::<Skills>();
+    let factions = ecs.read_storage::<Faction>();
+    let reputation = ecs.read_storage::<Reputation>();
+    let raws = RAWS.lock().unwrap();
+    let mut sounds = ecs.write_resource::<Sounds>();
     let mut swap_entities: Vec<(Entity, i32, i32)> = Vec::new();
     let map = ecs.fetch::<Map>();
+    // Only a move that actually happens costs a turn; walking into a wall shouldn't hand one out for free
+    let mut moved = false;
 
     for (entity, _p, viewshed, pos) in (&entities, &players, &mut viewseheds, &mut positions).join()
     {
+        let armour_weight: f32 = (&equipped, &wearables, &items)
+            .join()
+            .filter(|(worn, _, _)| worn.owner == entity)
+            .map(|(_, _, item)| item.weight)
+            .sum();
+        let stealth = skills.get(entity).map_or(0, |s| skill_bonus(Skill::Stealth, s));
+        let noise = movement_noise(armour_weight, stealth);
         let (dest_x, dest_y) = (pos.x + delta_x, pos.y + delta_y);
         if !map.is_tile_in_bounds(dest_x, dest_y) {
-            return;
+            return RunState::AwaitingInput;
         }
 
         for potential_target in map.tile_content[dest_x as usize][dest_y as usize].iter() {
             let bystander = bystanders.get(*potential_target);
             let vendor = vendors.get(*potential_target);
-            if bystander.is_some() || vendor.is_some() {
+            // Townsfolk who've turned on the player get fought, not talked to or traded with
+            let hostile = factions.get(*potential_target).is_some_and(|f| {
+                reaction_to(&raws, &f.name, "Player", reputation.get(entity)) == Reaction::Attack
+            });
+            let peaceful = (bystander.is_some() || vendor.is_some()) && !hostile;
+            if peaceful && dialogues.get(*potential_target).is_some() {
+                return RunState::ShowDialogue { speaker: *potential_target, node: 0 };
+            }
+            if let Some(vendor) = vendor.filter(|_| peaceful) {
+                if !is_open(vendor, &ecs.fetch::<GameClock>()) {
+                    let name = names.get(*potential_target).map_or("The vendor", |n| n.name.as_str());
+                    ecs.fetch_mut::<GameLog>().add(LogCategory::Dialogue, closed_message(vendor, name));
+                    return RunState::AwaitingInput;
+                }
+                return RunState::ShowVendor { vendor: *potential_target, mode: VendorMode::Buy };
+            }
+            if peaceful {
                 swap_entities.push((*potential_target, pos.x, pos.y));
                 pos.x = min(map.width - 1, max(0, pos.x + delta_x));
                 pos.y = min(map.height - 1, max(0, pos.y + delta_y));
                 entity_moved
                     .insert(entity, EntityMoved {})
                     .expect("Unable to insert marker");
+                Energy::spend(&mut energy, entity, MOVE_COST);
+                sounds.emit(entity, pos.x, pos.y, noise);
+                moved = true;
 
                 viewshed.dirty = true;
                 let mut ppos = ecs.write_resource::<Point>();
@@ -208,18 +307,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
                             },
                         )
                         .expect("Failed to add target");
-                    return;
+                    return RunState::PlayerTurn;
                 }
             }
-            let door = doors.get_mut(*potential_target);
-            if let Some(door) = door {
-                door.open = true;
-                blocks_visibility.remove(*potential_target);
-                blocks_movement.remove(*potential_target);
-                let glyph = renderables.get_mut(*potential_target).unwrap();
-                glyph.glyph = to_cp437('/');
-                viewshed.dirty = true;
-            }
         }
         if !map.blocked[dest_x as usize][dest_y as usize] {
             pos.x = min(map.width - 1, max(0, dest_x));
@@ -232,6 +322,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             entity_moved
                 .insert(entity, EntityMoved {})
                 .expect("Unable to insert marker");
+            Energy::spend(&mut energy, entity, MOVE_COST);
+            sounds.emit(entity, pos.x, pos.y, noise);
+            moved = true;
         }
     }
     for m in swap_entities.iter() {
@@ -241,9 +334,195 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             their_pos.y = m.2;
         }
     }
+    if moved {
+        RunState::PlayerTurn
+    } else {
+        RunState::AwaitingInput
+    }
 }
 
-fn get_item(ecs: &mut World) {
+// Picks out the door to open or close when there's only one candidate next to the player, and
+// otherwise asks which way
+fn door_command(ecs: &mut World, open: bool) -> RunState {
+    let candidates: Vec<Point> = {
+        let ppos = ecs.fetch::<Point>();
+        let map = ecs.fetch::<Map>();
+        let doors = ecs.read_storage::<Door>();
+        let mut candidates = Vec::new();
+        for (dx, dy) in DIRECTIONS.iter() {
+            let (x, y) = (ppos.x + dx, ppos.y + dy);
+            if !map.is_tile_in_bounds(x, y) {
+                continue;
+            }
+            if map.tile_content[x as usize][y as usize]
+                .iter()
+                .any(|e| doors.get(*e).is_some_and(|d| d.open != open))
+            {
+                candidates.push(Point::new(x, y));
+            }
+        }
+        candidates
+    };
+    let verb = if open { "open" } else { "close" };
+    match candidates.len() {
+        0 => {
+            ecs.fetch_mut::<GameLog>().add(LogCategory::System, format!("There is no door to {} here.", verb));
+            RunState::AwaitingInput
+        }
+        1 => operate_door(ecs, candidates[0].x, candidates[0].y, open).unwrap_or(RunState::AwaitingInput),
+        _ => {
+            ecs.fetch_mut::<GameLog>().add(LogCategory::System, format!("Which door do you want to {}?", verb));
+            RunState::ChooseDoor { open }
+        }
+    }
+}
+
+pub fn door_direction_input(gs: &mut State, ctx: &mut BTerm, open: bool) -> RunState {
+    let (dx, dy) = {
+        let bindings = gs.ecs.fetch::<KeyBindings>();
+        if bindings.pressed(Action::MenuCancel, ctx) {
+            return RunState::AwaitingInput;
+        }
+        match bindings.direction(ctx) {
+            None => return RunState::ChooseDoor { open },
+            Some(direction) => direction,
+        }
+    };
+    let target = {
+        let ppos = gs.ecs.fetch::<Point>();
+        Point::new(ppos.x + dx, ppos.y + dy)
+    };
+    operate_door(&mut gs.ecs, target.x, target.y, open).unwrap_or_else(|| {
+        gs.ecs.fetch_mut::<GameLog>().add(LogCategory::System, "There is no door there.");
+        RunState::AwaitingInput
+    })
+}
+
+pub fn look_command(ecs: &World) -> RunState {
+    let ppos = ecs.fetch::<Point>();
+    RunState::Look { x: ppos.x, y: ppos.y }
+}
+
+/// Moves the look cursor, or with TAB jumps it to the next visible thing, nearest first
+pub fn look_input(gs: &mut State, ctx: &mut BTerm, x: i32, y: i32) -> RunState {
+    let (dx, dy) = {
+        let bindings = gs.ecs.fetch::<KeyBindings>();
+        if bindings.pressed(Action::MenuCancel, ctx) || bindings.pressed(Action::Look, ctx) {
+            return RunState::AwaitingInput;
+        }
+        if bindings.pressed(Action::MenuNext, ctx) {
+            let next = next_visible_thing(&gs.ecs, x, y);
+            return RunState::Look { x: next.x, y: next.y };
+        }
+        match bindings.direction(ctx) {
+            None => return RunState::Look { x, y },
+            Some(direction) => direction,
+        }
+    };
+    let map = gs.ecs.fetch::<Map>();
+    if map.is_tile_in_bounds(x + dx, y + dy) {
+        RunState::Look { x: x + dx, y: y + dy }
+    } else {
+        RunState::Look { x, y }
+    }
+}
+
+fn next_visible_thing(ecs: &World, x: i32, y: i32) -> Point {
+    let player = *ecs.fetch::<Entity>();
+    let ppos = *ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let entities = ecs.entities();
+
+    let mut spots: Vec<Point> = (&entities, &positions, !&hidden)
+        .join()
+        .filter(|(e, pos, _)| *e != player && map.visible_tiles[pos.x as usize][pos.y as usize])
+        .map(|(_, pos, _)| Point::new(pos.x, pos.y))
+        .collect();
+    spots.sort_by_key(|p| ((p.x - ppos.x).pow(2) + (p.y - ppos.y).pow(2), p.x, p.y));
+    spots.dedup();
+    match spots.iter().position(|p| p.x == x && p.y == y) {
+        Some(i) => spots[(i + 1) % spots.len()],
+        None => spots.first().copied().unwrap_or(ppos),
+    }
+}
+
+/// Opens or closes the door at (x, y). A locked door is unlocked on the way if the player has the key,
+/// and otherwise the player has a go at picking the lock. Returns None if there's no door there that
+/// needs opening or closing.
+fn operate_door(ecs: &mut World, x: i32, y: i32, open: bool) -> Option<RunState> {
+    let player = *ecs.fetch::<Entity>();
+    let mut map = ecs.fetch_mut::<Map>();
+    if !map.is_tile_in_bounds(x, y) {
+        return None;
+    }
+    let mut door_storages: DoorStorages = (
+        ecs.write_storage::<Door>(),
+        ecs.write_storage::<BlocksTile>(),
+        ecs.write_storage::<BlocksVisibility>(),
+        ecs.write_storage::<Renderable>(),
+    );
+    let door = door_at(&map, &door_storages, x, y)?;
+    if door_storages.0.get(door).is_none_or(|d| d.open == open) {
+        return None;
+    }
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    let mut energy = ecs.write_storage::<Energy>();
+    let mut sounds = ecs.write_resource::<Sounds>();
+    if !open && !doorway_clear(&map, door, x, y) {
+        gamelog.add(LogCategory::System, "Something is in the way.");
+        return Some(RunState::AwaitingInput);
+    }
+
+    let mut locks = ecs.write_storage::<Locked>();
+    if let Some(lock) = locks.get(door).cloned() {
+        let key = {
+            let backpack = ecs.read_storage::<InBackpack>();
+            let keys = ecs.read_storage::<Key>();
+            let names = ecs.read_storage::<Name>();
+            (&backpack, &keys, &names)
+                .join()
+                .find(|(carried, key, _)| carried.owner == player && key.lock_id == lock.lock_id)
+                .map(|(_, _, name)| name.name.clone())
+        };
+        match key {
+            Some(key) => gamelog.add(LogCategory::System, format!("You unlock the door with the {}.", key)),
+            None => {
+                let skill = ecs.read_storage::<Skills>().get(player).map_or(0, |s| skill_bonus(Skill::Lockpicking, s));
+                let quickness = ecs.read_storage::<Attributes>().get(player).map_or(0, |a| a.quickness.bonus);
+                let roll = ecs.write_resource::<RandomNumberGenerator>().roll_dice(1, 20);
+                let success = roll + skill + quickness >= lock.difficulty;
+                if success {
+                    locks.remove(door);
+                    gamelog.add(LogCategory::System, "You pick the lock.");
+                } else {
+                    gamelog.add(LogCategory::System, "The door is locked, and the lock resists your picking.");
+                }
+                ecs.write_resource::<GameEvents>().publish(GameEvent::LockPicked { by: player, success, position: Point::new(x, y) });
+                Energy::spend(&mut energy, player, MOVE_COST);
+                sounds.emit(player, x, y, LOCKPICK_NOISE);
+                return Some(RunState::PlayerTurn);
+            }
+        }
+        locks.remove(door);
+    }
+
+    set_door(&mut map, &mut door_storages, door, x, y, open);
+    if open {
+        ecs.write_resource::<GameEvents>().publish(GameEvent::DoorOpened { by: player, position: Point::new(x, y) });
+    }
+    if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(player) {
+        viewshed.dirty = true;
+    }
+    Energy::spend(&mut energy, player, MOVE_COST);
+    sounds.emit(player, x, y, DOOR_NOISE);
+    Some(RunState::PlayerTurn)
+}
+
+// Picking up costs energy once the collection system handles the intent, so there's only a turn to
+// take when there's something here
+fn get_item(ecs: &mut World) -> RunState {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
@@ -259,9 +538,10 @@ fn get_item(ecs: &mut World) {
     }
 
     match target_item {
-        None => gameplog
-            .entries
-            .push("There is nothing here to pick  up".to_string()),
+        None => {
+            gameplog.add(LogCategory::Loot, "There is nothing here to pick  up");
+            RunState::AwaitingInput
+        }
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickUpItem>();
             pickup
@@ -273,6 +553,64 @@ fn get_item(ecs: &mut World) {
                     },
                 )
                 .expect("Unable to insert want to pickup");
+            RunState::PlayerTurn
         }
     }
 }
+
+pub fn has_pending_level_up(ecs: &World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let pools = ecs.read_storage::<Pools>();
+    match pools.get(*player_entity) {
+        None => false,
+        Some(p) => p.xp >= xp_for_next_level(p.level),
+    }
+}
+
+pub fn apply_level_up(ecs: &mut World, selection: LevelUpSelection) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut skills = ecs.write_storage::<Skills>();
+    let mut pools = ecs.write_storage::<Pools>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let attrs = attributes.get_mut(*player_entity).unwrap();
+    match selection {
+        LevelUpSelection::Might => improve_attribute(&mut attrs.might),
+        LevelUpSelection::Fitness => improve_attribute(&mut attrs.fitness),
+        LevelUpSelection::Quickness => improve_attribute(&mut attrs.quickness),
+        LevelUpSelection::Intelligence => improve_attribute(&mut attrs.intelligence),
+        LevelUpSelection::Skill(skill) => {
+            let player_skills = skills.get_mut(*player_entity).unwrap();
+            let trained = skill_bonus(skill, player_skills) + 1;
+            player_skills.skills.insert(skill, trained);
+        }
+    }
+
+    let player_pools = pools.get_mut(*player_entity).unwrap();
+    player_pools.level += 1;
+    player_pools.hit_points.max = player_hp_at_level(
+        attrs.fitness.base + attrs.fitness.modifiers,
+        player_pools.level,
+    );
+    player_pools.hit_points.current = player_pools.hit_points.max;
+    player_pools.mana.max = mana_at_level(
+        attrs.intelligence.base + attrs.intelligence.modifiers,
+        player_pools.level,
+    );
+    player_pools.mana.current = player_pools.mana.max;
+    // Might sets how much can be carried, so encumbrance needs working out again
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(*player_entity, EquipmentChanged {})
+        .expect("Unable to insert marker");
+
+    gamelog.add(LogCategory::System, format!(
+        "Congratulations, you are now level {}! Max HP is {} and max mana is {}.",
+        player_pools.level, player_pools.hit_points.max, player_pools.mana.max
+    ));
+}
+
+fn improve_attribute(attribute: &mut Attribute) {
+    attribute.base += 1;
+    attribute.bonus = attr_bonus(attribute.base + attribute.modifiers);
+}
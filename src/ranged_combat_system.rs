@@ -0,0 +1,161 @@
+use bracket_lib::color::{BLACK, CYAN, RGB};
+use bracket_lib::prelude::{to_cp437, Point};
+use bracket_lib::random::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::components::{Attributes, Energy, EquipmentSlot, Equipped, HungerClock, HungerState, MeleeWeapon, Name, NaturalAttackDefense, Overloaded, Pools, RangedWeapon, Shield, Skills, SufferDamage, WantsToShoot, Wearable};
+use crate::energy_system::SHOOT_COST;
+use crate::gamelog::{GameLog, LogBuilder, LogCategory};
+use crate::map::Map;
+use crate::melee_combat_system::{armor_class, attack_hits, damage_roll, hit_roll, natural_ranged_attack, shield_block};
+use crate::particle_system::ParticleBuilder;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, Pools>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Wearable>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        ReadStorage<'a, Shield>,
+        ReadStorage<'a, Overloaded>,
+        WriteStorage<'a, Energy>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            map,
+            mut gamelog,
+            mut rng,
+            mut particle_builder,
+            mut wants_shoot,
+            names,
+            attributes,
+            skills,
+            pools,
+            mut inflict_damage,
+            hunger_clock,
+            equipped_items,
+            melee_weapons,
+            ranged_weapons,
+            wearables,
+            natural,
+            shields,
+            overloaded,
+            mut energy
+        ) = data;
+
+        for (entity, shot, name, attacker_attrs, attacker_skills, attacker_pools) in (&entities, &wants_shoot, &names, &attributes, &skills, &pools).join() {
+            Energy::spend(&mut energy, entity, SHOOT_COST);
+            let weapon = match ranged_weapon(entity, &equipped_items, &melee_weapons, &ranged_weapons, &natural) {
+                None => continue,
+                Some((weapon, _)) => weapon,
+            };
+            let target = match shot_target(&map, shot.target, &pools) {
+                None => {
+                    if entity == *player_entity {
+                        gamelog.add(LogCategory::Combat, "You shoot, but there's nothing there to hit.");
+                    }
+                    continue;
+                }
+                Some(target) => target,
+            };
+            if attacker_pools.hit_points.current < 1 {
+                continue;
+            }
+            let (target_name, target_attrs, target_skills) = match (names.get(target), attributes.get(target), skills.get(target)) {
+                (Some(name), Some(attrs), Some(skills)) => (name, attrs, skills),
+                _ => continue,
+            };
+
+            let well_fed = hunger_clock.get(entity).is_some_and(|hc| hc.state == HungerState::WellFed);
+            let (natural_roll, modified_hit_roll) = hit_roll(&mut rng, attacker_attrs, attacker_skills, &weapon, well_fed, overloaded.get(entity).is_some());
+            let mut armor_item_bonus = 0.0;
+            for (worn, armor) in (&equipped_items, &wearables).join() {
+                if worn.owner == target {
+                    armor_item_bonus += armor.armor_class;
+                }
+            }
+            let armor_class = armor_class(natural.get(target), target_attrs, target_skills, armor_item_bonus, overloaded.get(target).is_some());
+
+            if attack_hits(natural_roll, modified_hit_roll, armor_class) {
+                let mut damage = damage_roll(&mut rng, attacker_attrs, attacker_skills, &weapon);
+                let mut blocked = 0;
+                for (wielded, shield) in (&equipped_items, &shields).join() {
+                    if wielded.owner == target && wielded.slot == EquipmentSlot::Shield {
+                        if let Some(reduction) = shield_block(&mut rng, shield, target_skills) {
+                            blocked = i32::min(damage, reduction);
+                        }
+                    }
+                }
+                damage -= blocked;
+                if blocked > 0 && damage == 0 {
+                    gamelog.add(LogCategory::Combat, format!("{} blocks {}'s shot.", &target_name.name, &name.name));
+                } else {
+                    SufferDamage::new_damage(&mut inflict_damage, target, damage, entity == *player_entity, Some(entity));
+                    LogBuilder::new(LogCategory::Combat)
+                        .name(&name.name)
+                        .append(" shoots ")
+                        .name(&target_name.name)
+                        .append(" for ")
+                        .damage(damage)
+                        .append(" hp.")
+                        .log(&mut gamelog);
+                }
+            } else {
+                LogBuilder::new(LogCategory::Combat)
+                    .name(&name.name)
+                    .append(" shoots at ")
+                    .name(&target_name.name)
+                    .append(", but misses.")
+                    .log(&mut gamelog);
+            }
+            particle_builder.request(shot.target.x, shot.target.y, RGB::named(CYAN), RGB::named(BLACK), to_cp437('*'), 200.0);
+        }
+        wants_shoot.clear();
+    }
+}
+
+/// The weapon an entity shoots with and its range: a wielded ranged weapon, or failing that a
+/// natural ranged attack
+pub fn ranged_weapon(
+    entity: Entity,
+    equipped: &ReadStorage<Equipped>,
+    melee_weapons: &ReadStorage<MeleeWeapon>,
+    ranged_weapons: &ReadStorage<RangedWeapon>,
+    natural: &ReadStorage<NaturalAttackDefense>,
+) -> Option<(MeleeWeapon, i32)> {
+    for (wielded, stats, ranged) in (equipped, melee_weapons, ranged_weapons).join() {
+        if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
+            return Some((stats.clone(), ranged.range));
+        }
+    }
+    natural.get(entity).and_then(natural_ranged_attack)
+}
+
+fn shot_target(map: &Map, target: Point, pools: &ReadStorage<Pools>) -> Option<Entity> {
+    if !map.is_tile_in_bounds(target.x, target.y) {
+        return None;
+    }
+    map.tile_content[target.x as usize][target.y as usize]
+        .iter()
+        .find(|e| pools.get(**e).is_some_and(|p| p.hit_points.current > 0))
+        .copied()
+}
@@ -0,0 +1,22 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct DialogueTree {
+    pub id: String,
+    pub nodes: Vec<DialogueNode>
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DialogueNode {
+    pub id: String,
+    pub text: String,
+    pub choices: Vec<DialogueChoice>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DialogueChoice {
+    pub text: String,
+    pub next: Option<String>,
+    pub requires: Option<Vec<String>>,
+    pub actions: Option<Vec<String>>
+}
@@ -0,0 +1,9 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct FactionInfo {
+    pub name: String,
+    pub responses: HashMap<String, String>
+}
@@ -7,10 +7,16 @@ use crate::components::EquipmentSlot;
 pub struct Item {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
     pub renderable: Option<Renderable>,
+    pub weight: Option<f32>,
+    pub value: Option<f32>,
+    pub vendor_category: Option<String>,
+    pub key: Option<String>,
     pub consumable: Option<Consumable>,
     pub weapon: Option<Weapon>,
     pub wearable: Option<Wearable>,
+    pub shield: Option<Shield>,
     pub artefact: Option<Artefact>
 }
 
@@ -32,7 +38,8 @@ pub struct Weapon {
     pub range: String,
     pub attribute: String,
     pub base_damage: String,
-    pub hit_bonus: i32
+    pub hit_bonus: i32,
+    pub two_handed: Option<bool>
 }
 
 #[derive(Deserialize, Debug)]
@@ -41,6 +48,11 @@ pub struct Wearable {
     pub armor_class: f32
 }
 
+#[derive(Deserialize, Debug)]
+pub struct Shield {
+    pub block_chance: i32,
+    pub damage_reduction: i32
+}
 
 #[derive(Deserialize, Debug)]
 pub struct Artefact {
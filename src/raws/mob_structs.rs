@@ -6,18 +6,29 @@ use crate::raws::item_structs::Renderable;
 pub struct Mob {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
     pub renderable: Option<Renderable>,
     pub blocks_tile: bool,
+    pub opens_doors: Option<bool>,
     pub vision_range: i32,
     pub ai: String,
+    pub behaviour: Option<Vec<MobBehaviour>>,
+    pub sleep_chance: Option<i32>,
+    pub faction: Option<String>,
+    pub pack_size: Option<String>,
     pub quips: Option<Vec<String>>,
+    pub dialogue: Option<String>,
+    pub vendor: Option<Vec<String>>,
+    pub shop_hours: Option<(i32, i32)>,
+    pub schedule: Option<Vec<MobScheduleEntry>>,
     pub attributes: MobAttributes,
     pub skills: Option<HashMap<String, i32>>,
     pub level: Option<i32>,
     pub hp: Option<i32>,
     pub mana: Option<i32>,
     pub equipped: Option<Vec<String>>,
-    pub natural: Option<MobNatural>
+    pub natural: Option<MobNatural>,
+    pub spells: Option<Vec<String>>
 }
 
 #[derive(Deserialize, Debug)]
@@ -30,7 +41,8 @@ pub struct MobNatural {
 pub struct NaturalAttack {
     pub name: String,
     pub hit_bonus: i32,
-    pub damage: String
+    pub damage: String,
+    pub range: Option<i32>
 }
 
 #[derive(Deserialize, Debug)]
@@ -40,3 +52,15 @@ pub struct MobAttributes {
     pub quickness: Option<i32>,
     pub intelligence: Option<i32>,
 }
+
+#[derive(Deserialize, Debug)]
+pub struct MobScheduleEntry {
+    pub hour: i32,
+    pub place: String
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MobBehaviour {
+    pub action: String,
+    pub when: Option<String>
+}
@@ -6,16 +6,22 @@ use serde::Deserialize;
 
 use rawmaster::*;
 
+use crate::raws::dialogue_structs::DialogueTree;
+use crate::raws::faction_structs::FactionInfo;
 use crate::raws::item_structs::{Item};
 use crate::raws::mob_structs::Mob;
 use crate::raws::prop_structs::Prop;
 use crate::raws::spawn_table_structs::SpawnTableEntry;
+use crate::raws::spell_structs::Spell;
 
+pub mod dialogue_structs;
+mod faction_structs;
 mod item_structs;
 pub mod rawmaster;
 mod mob_structs;
 mod prop_structs;
 mod spawn_table_structs;
+mod spell_structs;
 
 embedded_resource!(RAW_FILE, "../../raws/spawns.json");
 
@@ -40,5 +46,8 @@ pub struct Raws {
     pub items: Vec<Item>,
     pub mobs: Vec<Mob>,
     pub props: Vec<Prop>,
-    pub spawn_table: Vec<SpawnTableEntry>
+    pub spells: Vec<Spell>,
+    pub spawn_table: Vec<SpawnTableEntry>,
+    pub faction_table: Vec<FactionInfo>,
+    pub dialogues: Vec<DialogueTree>
 }
@@ -6,14 +6,22 @@ use crate::raws::item_structs::Renderable;
 pub struct Prop {
     pub id: String,
     pub name: String,
+    pub description: Option<String>,
     pub renderable: Option<Renderable>,
     pub hidden: Option<bool>,
     pub blocks_tile: Option<bool>,
     pub blocks_visibility: Option<bool>,
     pub door_open: Option<bool>,
+    pub lock: Option<Lock>,
     pub entry_trigger: Option<EntryTrigger>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct Lock {
+    pub id: String,
+    pub difficulty: i32
+}
+
 #[derive(Deserialize, Debug)]
 pub struct EntryTrigger {
     pub effects: HashMap<String, String>
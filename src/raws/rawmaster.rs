@@ -8,13 +8,17 @@ use regex::Regex;
 use specs::{Builder, Entity, EntityBuilder, World, WorldExt};
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
-use crate::components::{AreaOfEffect, Artefact, Attribute, Attributes, BlocksTile, BlocksVisibility, Bystander, Confusion, Consumable, Door, EntryTrigger, EquipmentSlot, Equippable, Equipped, Hidden, InBackpack, InflictsDamage, MagicMapper, MeleeWeapon, Monster, Name, NaturalAttack, NaturalAttackDefense, Pool, Pools, Position, ProvidesFood, ProvidesHealing, Quips, Ranged, SerializeMe, SingleActivation, Skill, Skills, Vendor, Viewshed, WeaponAttribute, Wearable};
+use crate::components::{AreaOfEffect, Artefact, Asleep, Attribute, Attributes, Behaviour, BehaviourAction, BehaviourCondition, BehaviourRule, BlocksTile, BlocksVisibility, Bystander, Confusion, Dialogue, Consumable, Door, Energy, EntryTrigger, EquipmentChanged, EquipmentSlot, Equippable, Equipped, Examinable, Faction, Hidden, Home, InBackpack, InflictsDamage, Key, KnownSpell, Locked, MagicMapper, MeleeWeapon, Memory, Monster, Name, NaturalAttack, NaturalAttackDefense, OpensDoors, Pool, Pools, Position, ProvidesFood, ProvidesHealing, Quips, Ranged, RangedWeapon, Reaction, Regeneration, Schedule, ScheduleEntry, SchedulePlace, SerializeMe, Shield, SingleActivation, Skill, Skills, TeachesSpell, TwoHanded, Vendor, Viewshed, WeaponAttribute, Wearable};
+use crate::energy_system::ACTION_THRESHOLD;
 use crate::gamesystem::{attr_bonus, mana_at_level, npc_hp};
 use crate::random_tables::RandomTable;
 use crate::raws::rawmaster::SpawnType::AtPosition;
 use crate::raws::Raws;
+use crate::raws::dialogue_structs::DialogueNode;
+use crate::raws::mob_structs::{MobBehaviour, MobScheduleEntry};
 use crate::raws::spawn_table_structs::SpawnTableEntry;
 use crate::util::namegen::{generate_artefact_name, generate_ogur_name};
+use crate::vendor_system::{RESTOCK_TURNS, STOCK_LEVEL};
 
 pub enum SpawnType {
     AtPosition { x: i32, y: i32 },
@@ -27,15 +31,21 @@ pub struct RawMaster {
     item_index: HashMap<String, usize>,
     mob_index: HashMap<String, usize>,
     prop_index: HashMap<String, usize>,
+    spell_index: HashMap<String, usize>,
+    faction_index: HashMap<String, HashMap<String, Reaction>>,
+    dialogue_index: HashMap<String, usize>,
 }
 
 impl RawMaster {
     pub fn empty() -> Self {
         Self {
-            raws: Raws { items: Vec::new(), mobs: Vec::new(), props: Vec::new(), spawn_table: Vec::new() },
+            raws: Raws { items: Vec::new(), mobs: Vec::new(), props: Vec::new(), spells: Vec::new(), spawn_table: Vec::new(), faction_table: Vec::new(), dialogues: Vec::new() },
             item_index: HashMap::new(),
             mob_index: HashMap::new(),
             prop_index: HashMap::new(),
+            spell_index: HashMap::new(),
+            faction_index: HashMap::new(),
+            dialogue_index: HashMap::new(),
         }
     }
 
@@ -63,6 +73,34 @@ impl RawMaster {
             }
             self.prop_index.insert(prop.id.to_string(), i);
         }
+        for (i, spell) in self.raws.spells.iter().enumerate() {
+            if self.spell_index.contains_key(&spell.id) {
+                console::log(format!("WARNING - duplicate spell type in raw file [{}]", spell.id))
+            }
+            self.spell_index.insert(spell.id.to_string(), i);
+        }
+        for faction in self.raws.faction_table.iter() {
+            let mut reactions: HashMap<String, Reaction> = HashMap::new();
+            for (other, response) in faction.responses.iter() {
+                let reaction = match response.as_str() {
+                    "attack" => Reaction::Attack,
+                    "flee" => Reaction::Flee,
+                    "ignore" => Reaction::Ignore,
+                    _ => {
+                        console::log(format!("WARNING - unknown faction response [{}] in faction [{}]", response, faction.name));
+                        Reaction::Ignore
+                    }
+                };
+                reactions.insert(other.clone(), reaction);
+            }
+            self.faction_index.insert(faction.name.clone(), reactions);
+        }
+        for (i, dialogue) in self.raws.dialogues.iter().enumerate() {
+            if self.dialogue_index.contains_key(&dialogue.id) {
+                console::log(format!("WARNING - duplicate dialogue tree in raw file [{}]", dialogue.id))
+            }
+            self.dialogue_index.insert(dialogue.id.to_string(), i);
+        }
     }
 }
 
@@ -87,6 +125,41 @@ fn get_renderable_component(renderable: &super::item_structs::Renderable) -> cra
     }
 }
 
+fn apply_effects<'a>(effects: &HashMap<String, String>, new_entity: EntityBuilder<'a>) -> EntityBuilder<'a> {
+    let mut eb = new_entity;
+    for effect in effects.iter() {
+        let effect_name = effect.0.as_str();
+        match effect_name {
+            "provides_healing" => {
+                eb = eb.with(ProvidesHealing { heal_amount: effect.1.parse::<i32>().unwrap() })
+            }
+            "ranged" => {
+                eb = eb.with(Ranged { range: effect.1.parse::<i32>().unwrap() })
+            }
+            "damage" => {
+                eb = eb.with(InflictsDamage { damage: effect.1.parse::<i32>().unwrap() })
+            }
+            "area_of_effect" => {
+                eb = eb.with(AreaOfEffect { radius: effect.1.parse::<i32>().unwrap() })
+            }
+            "confusion" => {
+                eb = eb.with(Confusion { turns: effect.1.parse::<i32>().unwrap() })
+            }
+            "magic_mapping" => {
+                eb = eb.with(MagicMapper {})
+            }
+            "food" => {
+                eb = eb.with(ProvidesFood { points: effect.1.parse::<i32>().unwrap() })
+            }
+            "teach_spell" => {
+                eb = eb.with(TeachesSpell { spell: effect.1.clone() })
+            }
+            _ => console::log(format!("Warning: consumable effect {} not implemented!", effect_name))
+        }
+    }
+    eb
+}
+
 pub fn spawn_named_entity(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut World) -> Option<Entity> {
     if raws.item_index.contains_key(key) {
         return spawn_named_item(raws, key, pos, ecs);
@@ -121,38 +194,22 @@ pub fn spawn_named_item(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mu
             eb = eb.with(get_renderable_component(renderable));
         }
         eb = eb.with(Name { name: item_template.name.clone() });
+        if let Some(description) = &item_template.description {
+            eb = eb.with(Examinable { description: description.clone() });
+        }
 
-        eb = eb.with(crate::components::Item {});
+        eb = eb.with(crate::components::Item {
+            weight: item_template.weight.unwrap_or(0.0),
+            value: item_template.value.unwrap_or(0.0)
+        });
+
+        if let Some(lock_id) = &item_template.key {
+            eb = eb.with(Key { lock_id: lock_id.clone() });
+        }
 
         if let Some(consumable) = &item_template.consumable {
             eb = eb.with(Consumable {});
-            for effect in consumable.effects.iter() {
-                let effect_name = effect.0.as_str();
-                match effect_name {
-                    "provides_healing" => {
-                        eb = eb.with(ProvidesHealing { heal_amount: effect.1.parse::<i32>().unwrap() })
-                    }
-                    "ranged" => {
-                        eb = eb.with(Ranged { range: effect.1.parse::<i32>().unwrap() })
-                    }
-                    "damage" => {
-                        eb = eb.with(InflictsDamage { damage: effect.1.parse::<i32>().unwrap() })
-                    }
-                    "area_of_effect" => {
-                        eb = eb.with(AreaOfEffect { radius: effect.1.parse::<i32>().unwrap() })
-                    }
-                    "confusion" => {
-                        eb = eb.with(Confusion { turns: effect.1.parse::<i32>().unwrap() })
-                    }
-                    "magic_mapping" => {
-                        eb = eb.with(MagicMapper {})
-                    }
-                    "food" => {
-                        eb = eb.with(ProvidesFood { points: effect.1.parse::<i32>().unwrap() })
-                    }
-                    _ => console::log(format!("Warning: consumable effect {} not implemented!", effect_name))
-                }
-            }
+            eb = apply_effects(&consumable.effects, eb);
         }
 
         if let Some(weapon) = &item_template.weapon {
@@ -169,12 +226,20 @@ pub fn spawn_named_item(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mu
                 hit_bonus: weapon.hit_bonus
             };
             eb = eb.with(wpn);
+            if weapon.two_handed.unwrap_or(false) {
+                eb = eb.with(TwoHanded {});
+            }
+            if let Ok(range) = weapon.range.parse::<i32>() {
+                eb = eb.with(RangedWeapon { range });
+            }
 
         }
         if let Some(wearable) = &item_template.wearable {
             eb = eb.with(Equippable { slot: wearable.slot });
-            eb = eb.with(Wearable { armor_class: wearable.armor_class})
-            // eb = eb.with(MeleeWeapon { attack: shield.defense_bonus });
+            eb = eb.with(Wearable { armor_class: wearable.armor_class});
+        }
+        if let Some(shield) = &item_template.shield {
+            eb = eb.with(Shield { block_chance: shield.block_chance, damage_reduction: shield.damage_reduction });
         }
 
         if let Some(artefact) = artefact {
@@ -185,6 +250,71 @@ pub fn spawn_named_item(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mu
     None
 }
 
+pub fn spawn_named_spell(raws: &RawMaster, key: &String, owner: Entity, ecs: &mut World) -> Option<Entity> {
+    if raws.spell_index.contains_key(key) {
+        let spell_template = &raws.raws.spells[raws.spell_index[key]];
+
+        let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
+        eb = eb.with(Name { name: spell_template.name.clone() });
+        eb = eb.with(KnownSpell { owner, mana_cost: spell_template.mana_cost });
+        eb = apply_effects(&spell_template.effects, eb);
+        return Some(eb.build());
+    }
+    None
+}
+
+pub fn spell_name(raws: &RawMaster, key: &String) -> Option<String> {
+    raws.spell_index.get(key).map(|i| raws.raws.spells[*i].name.clone())
+}
+
+/// How members of one faction treat members of another, falling back to the faction's "Default" response
+pub fn faction_reaction(my_faction: &str, their_faction: &str, raws: &RawMaster) -> Reaction {
+    match raws.faction_index.get(my_faction) {
+        None => Reaction::Ignore,
+        Some(reactions) => *reactions
+            .get(their_faction)
+            .or_else(|| reactions.get("Default"))
+            .unwrap_or(&Reaction::Ignore),
+    }
+}
+
+/// Looks up a node of a dialogue tree; the first node is where every conversation starts
+pub fn dialogue_node<'a>(raws: &'a RawMaster, tree: &str, node: usize) -> Option<&'a DialogueNode> {
+    raws.dialogue_index
+        .get(tree)
+        .and_then(|i| raws.raws.dialogues[*i].nodes.get(node))
+}
+
+pub fn dialogue_node_index(raws: &RawMaster, tree: &str, node_id: &str) -> Option<usize> {
+    raws.dialogue_index
+        .get(tree)
+        .and_then(|i| raws.raws.dialogues[*i].nodes.iter().position(|n| n.id == node_id))
+}
+
+/// Ids of the items a vendor dealing in these categories carries
+pub fn vendor_items(raws: &RawMaster, categories: &[String]) -> Vec<String> {
+    raws.raws.items
+        .iter()
+        .filter(|item| item.vendor_category.as_ref().is_some_and(|c| categories.contains(c)))
+        .map(|item| item.id.clone())
+        .collect()
+}
+
+pub fn item_name_and_value(raws: &RawMaster, key: &str) -> Option<(String, f32)> {
+    raws.item_index
+        .get(key)
+        .map(|i| &raws.raws.items[*i])
+        .map(|item| (item.name.clone(), item.value.unwrap_or(0.0)))
+}
+
+/// How many followers a mob brings along, as dice, if it comes in packs
+pub fn mob_pack_size(raws: &RawMaster, key: &String) -> Option<(i32, i32, i32)> {
+    raws.mob_index
+        .get(key)
+        .and_then(|i| raws.raws.mobs[*i].pack_size.as_ref())
+        .map(|dice| parse_dice_string(dice))
+}
+
 pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut World) -> Option<Entity> {
     if raws.mob_index.contains_key(key) {
         let mob_template = &raws.raws.mobs[raws.mob_index[key]];
@@ -197,7 +327,15 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
             },
             _ => Name { name: mob_template.name.clone() }
         };
-
+        let starting_energy = ecs.write_resource::<RandomNumberGenerator>().range(0, ACTION_THRESHOLD);
+        let starts_asleep = mob_template.sleep_chance.is_some_and(|chance| {
+            ecs.write_resource::<RandomNumberGenerator>().roll_dice(1, 100) <= chance
+        });
+
+        let home = match pos {
+            AtPosition { x, y } => Some(Home { x, y }),
+            _ => None,
+        };
         let mut eb = ecs.create_entity().marked::<SimpleMarker<SerializeMe>>();
 
         eb = spawn_position(pos, eb, key.clone(), raws);
@@ -207,13 +345,36 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
         }
 
         eb = eb.with(name);
+        if let Some(description) = &mob_template.description {
+            eb = eb.with(Examinable { description: description.clone() });
+        }
 
         match mob_template.ai.as_ref() {
             "melee" => eb = eb.with(Monster {}),
             "bystander" => eb = eb.with(Bystander {}),
-            "vendor" => eb = eb.with(Vendor {}),
+            "vendor" => {
+                let categories = mob_template.vendor.clone().unwrap_or_default();
+                let stock = vendor_items(raws, &categories)
+                    .into_iter()
+                    .map(|id| (id, STOCK_LEVEL))
+                    .collect();
+                let hours = mob_template.shop_hours.unwrap_or((0, 24));
+                eb = eb.with(Vendor { categories, stock, restock_timer: RESTOCK_TURNS, hours })
+            }
             _ => {}
         }
+        let rules = match &mob_template.behaviour {
+            Some(behaviour) => parse_behaviour(behaviour),
+            None => default_behaviour(&mob_template.ai),
+        };
+        eb = eb.with(Behaviour { rules });
+        if let Some(schedule) = &mob_template.schedule {
+            eb = eb.with(parse_schedule(schedule));
+        }
+        eb = eb.with(Faction { name: mob_template.faction.clone().unwrap_or("Monsters".to_string()) });
+        if let Some(home) = &home {
+            eb = eb.with(home.clone());
+        }
         let might = mob_template.attributes.might.unwrap_or(11);
         let fitness = mob_template.attributes.fitness.unwrap_or(11);
         let quickness = mob_template.attributes.quickness.unwrap_or(11);
@@ -235,6 +396,8 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
             mana: Pool { current: mob_mana, max: mob_mana},
             xp: 0,
             level: mob_level,
+            total_weight: 0.0,
+            gold: 0,
         };
         eb = eb.with(pools);
 
@@ -252,7 +415,22 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
             Skill::Magic,
             *template_skill_map.get("Magic").unwrap_or(&1)
         );
+        skill_map.insert(
+            Skill::Stealth,
+            *template_skill_map.get("Stealth").unwrap_or(&1)
+        );
+        skill_map.insert(
+            Skill::Lockpicking,
+            *template_skill_map.get("Lockpicking").unwrap_or(&1)
+        );
         eb = eb.with(Skills{ skills: skill_map });
+        eb = eb.with(Energy { current: starting_energy });
+        eb = eb.with(EquipmentChanged {});
+        eb = eb.with(Regeneration { hp_turns: 0, mana_turns: 0 });
+        eb = eb.with(Memory { last_seen: None, search_turns: 0 });
+        if starts_asleep {
+            eb = eb.with(Asleep {});
+        }
 
         if let Some(quips) = &mob_template.quips {
             eb = eb.with(
@@ -260,9 +438,16 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
             );
         }
 
+        if let Some(tree) = &mob_template.dialogue {
+            eb = eb.with(Dialogue { tree: tree.clone() });
+        }
+
         if mob_template.blocks_tile {
             eb = eb.with(BlocksTile {});
         }
+        if mob_template.opens_doors.unwrap_or(false) {
+            eb = eb.with(OpensDoors {});
+        }
         eb = eb.with(Viewshed { visible_tiles: Vec::new(), range: mob_template.vision_range, dirty: true });
 
         if let Some(na) = &mob_template.natural {
@@ -280,6 +465,7 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
                             damage_die_type: d,
                             damage_bonus: b,
                             hit_bonus: a.hit_bonus,
+                            range: a.range,
                         }
                     )
                 }
@@ -294,6 +480,11 @@ pub fn spawn_named_mob(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mut
                 spawn_named_entity(raws, id, SpawnType::Equipped {by: new_mob}, ecs);
             }
         }
+        if let Some(spells) = &mob_template.spells {
+            for id in spells.iter() {
+                spawn_named_spell(raws, id, new_mob, ecs);
+            }
+        }
 
 
         return Some(new_mob);
@@ -312,6 +503,9 @@ pub fn spawn_named_prop(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mu
             eb = eb.with(get_renderable_component(renderable));
         }
         eb = eb.with(Name { name: prop_template.name.clone() });
+        if let Some(description) = &prop_template.description {
+            eb = eb.with(Examinable { description: description.clone() });
+        }
         if let Some(hidden) = prop_template.hidden {
             if hidden {
                 eb = eb.with(Hidden {});
@@ -328,9 +522,10 @@ pub fn spawn_named_prop(raws: &RawMaster, key: &String, pos: SpawnType, ecs: &mu
             }
         }
         if let Some(door) = prop_template.door_open {
-            if door {
-                eb = eb.with(Door { open: door });
-            }
+            eb = eb.with(Door { open: door });
+        }
+        if let Some(lock) = &prop_template.lock {
+            eb = eb.with(Locked { lock_id: lock.id.clone(), difficulty: lock.difficulty });
         }
         if let Some(trigger) = &prop_template.entry_trigger {
             eb = eb.with(EntryTrigger {});
@@ -368,6 +563,92 @@ pub fn get_spawn_table_for_depth(raws: &RawMaster, depth: i32) -> RandomTable {
     rt
 }
 
+// "home" is where the mob was spawned, "anywhere" leaves it to the rest of its behaviour, and anything
+// else names one of the map's landmarks
+fn parse_schedule(schedule: &[MobScheduleEntry]) -> Schedule {
+    let mut entries: Vec<ScheduleEntry> = schedule
+        .iter()
+        .map(|entry| ScheduleEntry {
+            hour: entry.hour,
+            place: match entry.place.as_str() {
+                "home" => SchedulePlace::Home,
+                "anywhere" => SchedulePlace::Anywhere,
+                landmark => SchedulePlace::Landmark(landmark.to_string()),
+            },
+        })
+        .collect();
+    entries.sort_by_key(|entry| entry.hour);
+    Schedule { entries }
+}
+
+fn default_behaviour(ai: &str) -> Vec<BehaviourRule> {
+    let rule = |action| BehaviourRule { action, condition: BehaviourCondition::Always };
+    // Attacking and chasing only ever target factions we're hostile to, so peaceful folk leave everyone alone
+    match ai {
+        "melee" => vec![rule(BehaviourAction::Attack), rule(BehaviourAction::Chase), rule(BehaviourAction::Idle)],
+        "bystander" => vec![
+            rule(BehaviourAction::Attack),
+            rule(BehaviourAction::Chase),
+            rule(BehaviourAction::FollowSchedule),
+            rule(BehaviourAction::Wander),
+        ],
+        "vendor" => vec![rule(BehaviourAction::Attack), rule(BehaviourAction::FollowSchedule), rule(BehaviourAction::Idle)],
+        _ => vec![rule(BehaviourAction::Attack), rule(BehaviourAction::Idle)],
+    }
+}
+
+fn parse_behaviour(behaviour: &[MobBehaviour]) -> Vec<BehaviourRule> {
+    let mut rules = Vec::new();
+    for b in behaviour.iter() {
+        let (action_name, action_arg) = split_behaviour_arg(&b.action);
+        let action = match action_name {
+            "attack" => BehaviourAction::Attack,
+            "chase" => BehaviourAction::Chase,
+            "flee" => BehaviourAction::Flee,
+            "keep_distance" => BehaviourAction::KeepDistance(action_arg.unwrap_or(4)),
+            "guard_area" => BehaviourAction::GuardArea(action_arg.unwrap_or(6)),
+            "return_home" => BehaviourAction::ReturnHome,
+            "loot" => BehaviourAction::Loot,
+            "shoot" => BehaviourAction::Shoot,
+            "cast" => BehaviourAction::Cast,
+            "follow_schedule" => BehaviourAction::FollowSchedule,
+            "wander" => BehaviourAction::Wander,
+            "idle" => BehaviourAction::Idle,
+            _ => {
+                console::log(format!("Warning: behaviour action {} not implemented!", action_name));
+                continue;
+            }
+        };
+        let condition = match &b.when {
+            None => BehaviourCondition::Always,
+            Some(when) => {
+                let (condition_name, condition_arg) = split_behaviour_arg(when);
+                match condition_name {
+                    "always" => BehaviourCondition::Always,
+                    "sees_enemy" => BehaviourCondition::SeesEnemy,
+                    "enemy_within" => BehaviourCondition::EnemyWithin(condition_arg.unwrap_or(1)),
+                    "hp_below" => BehaviourCondition::HpBelow(condition_arg.unwrap_or(25)),
+                    "away_from_home" => BehaviourCondition::AwayFromHome(condition_arg.unwrap_or(0)),
+                    _ => {
+                        console::log(format!("Warning: behaviour condition {} not implemented!", condition_name));
+                        continue;
+                    }
+                }
+            }
+        };
+        rules.push(BehaviourRule { action, condition });
+    }
+    rules
+}
+
+// Behaviour strings look like "keep_distance:4", with an optional numeric argument
+fn split_behaviour_arg(raw: &str) -> (&str, Option<i32>) {
+    match raw.split_once(':') {
+        None => (raw, None),
+        Some((name, arg)) => (name, arg.trim().parse::<i32>().ok()),
+    }
+}
+
 pub fn parse_dice_string(dice: &str) -> (i32, i32, i32) {
     lazy_static! {
         static ref DICE_RE: Regex = Regex::new(r"(\d+)d(\d+)([\+\-]\d+)?").unwrap();
@@ -405,3 +686,54 @@ fn find_slot_for_equippable_item(tag: &String, raws: &RawMaster) -> EquipmentSlo
         panic!("Trying to equip {:?}, but it has not slot tag", tag);
     }
 }
+
+/// Raws holding nothing but the given factions and their responses, for tests that need a faction table
+#[cfg(test)]
+pub(crate) fn raws_with_factions(factions: &[(&str, &[(&str, &str)])]) -> RawMaster {
+    let faction_table = factions
+        .iter()
+        .map(|(name, responses)| crate::raws::faction_structs::FactionInfo {
+            name: name.to_string(),
+            responses: responses.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        })
+        .collect();
+    let mut raws = RawMaster::empty();
+    raws.load(Raws {
+        items: Vec::new(),
+        mobs: Vec::new(),
+        props: Vec::new(),
+        spells: Vec::new(),
+        spawn_table: Vec::new(),
+        faction_table,
+        dialogues: Vec::new(),
+    });
+    raws
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_responses_take_priority_over_the_default() {
+        let raws = raws_with_factions(&[
+            ("Orcs", &[("Default", "attack"), ("Orcs", "ignore"), ("Townsfolk", "flee")]),
+        ]);
+        assert_eq!(faction_reaction("Orcs", "Orcs", &raws), Reaction::Ignore);
+        assert_eq!(faction_reaction("Orcs", "Townsfolk", &raws), Reaction::Flee);
+        assert_eq!(faction_reaction("Orcs", "Player", &raws), Reaction::Attack);
+    }
+
+    #[test]
+    fn unknown_factions_and_missing_defaults_are_ignored() {
+        let raws = raws_with_factions(&[("Cattle", &[("Player", "flee")])]);
+        assert_eq!(faction_reaction("Cattle", "Orcs", &raws), Reaction::Ignore);
+        assert_eq!(faction_reaction("Dragons", "Player", &raws), Reaction::Ignore);
+    }
+
+    #[test]
+    fn unknown_responses_load_as_ignore() {
+        let raws = raws_with_factions(&[("Bandits", &[("Default", "haggle")])]);
+        assert_eq!(faction_reaction("Bandits", "Player", &raws), Reaction::Ignore);
+    }
+}
@@ -0,0 +1,11 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct Spell {
+    pub id: String,
+    pub name: String,
+    pub mana_cost: i32,
+    pub effects: HashMap<String, String>
+}
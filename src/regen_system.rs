@@ -0,0 +1,72 @@
+use specs::prelude::*;
+
+use crate::components::{Attributes, Energy, HungerClock, HungerState, MyTurn, Pools, Regeneration, Skill, Skills};
+use crate::energy_system::ACTION_THRESHOLD;
+use crate::gamesystem::{hp_regen_interval, mana_regen_interval, skill_bonus};
+use crate::RunState;
+
+pub struct RegenSystem {}
+
+impl<'a> System<'a> for RegenSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteStorage<'a, Regeneration>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, MyTurn>,
+        ReadStorage<'a, Energy>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut regeneration,
+            mut pools,
+            attributes,
+            skills,
+            hunger_clocks,
+            turns,
+            energy
+        ) = data;
+
+        for (entity, regen, pools) in (&entities, &mut regeneration, &mut pools).join() {
+            // The player only recovers on turns they've actually spent doing something
+            let proceed = if entity == *player_entity {
+                *runstate == RunState::PlayerTurn
+                    && energy.get(entity).is_none_or(|e| e.current < ACTION_THRESHOLD)
+            } else {
+                turns.get(entity).is_some()
+            };
+            if !proceed || pools.hit_points.current < 1 {
+                continue;
+            }
+
+            let hunger = hunger_clocks.get(entity).map_or(HungerState::Normal, |hc| hc.state);
+            let fitness_bonus = attributes.get(entity).map_or(0, |a| a.fitness.bonus);
+            let intelligence_bonus = attributes.get(entity).map_or(0, |a| a.intelligence.bonus);
+            let magic_skill = skills.get(entity).map_or(0, |s| skill_bonus(Skill::Magic, s));
+
+            regen.hp_turns += 1;
+            if let Some(interval) = hp_regen_interval(fitness_bonus, hunger) {
+                if regen.hp_turns >= interval {
+                    regen.hp_turns = 0;
+                    pools.hit_points.current = i32::min(pools.hit_points.current + 1, pools.hit_points.max);
+                }
+            }
+
+            regen.mana_turns += 1;
+            if let Some(interval) = mana_regen_interval(intelligence_bonus, magic_skill, hunger) {
+                if regen.mana_turns >= interval {
+                    regen.mana_turns = 0;
+                    pools.mana.current = i32::min(pools.mana.current + 1, pools.mana.max);
+                }
+            }
+        }
+    }
+}
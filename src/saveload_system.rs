@@ -7,7 +7,10 @@ use specs::{Builder, Entity, Join, World, WorldExt};
 use specs::error::NoError;
 use specs::saveload::{DeserializeComponents, MarkedBuilder, SerializeComponents, SimpleMarker, SimpleMarkerAllocator};
 
-use crate::components::{AreaOfEffect, Artefact, Attributes, BlocksTile, BlocksVisibility, Bystander, Confusion, Consumable, Wearable, Door, EntityMoved, EntryTrigger, Equippable, Equipped, Examinable, Hidden, HungerClock, InBackpack, InflictsDamage, Item, MagicMapper, MeleeWeapon, Monster, Name, ParticleLifetime, Player, Pools, Position, ProvidesFood, ProvidesHealing, Quips, Ranged, Renderable, SerializationHelper, SerializeMe, SingleActivation, Skills, SufferDamage, Vendor, Viewshed, WantsToDropItem, WantsToMelee, WantsToPickUpItem, WantsToUnequipItem, WantsToUseItem, NaturalAttackDefense};
+use crate::components::{Locked, Key, OpensDoors, AreaOfEffect, Schedule, Dialogue, Quests, PackMember, RangedWeapon, WantsToShoot, Faction, Reputation, Asleep, Memory, Behaviour, Home, Artefact, Attributes, BlocksTile, BlocksVisibility, Bystander, Confusion, Consumable, Wearable, Door, Energy, EntityMoved, EquipmentChanged, EntryTrigger, Equippable, Equipped, Examinable, Hidden, HungerClock, InBackpack, InflictsDamage, Item, KnownSpell, MagicMapper, MeleeWeapon, Monster, MyTurn, Name, Overloaded, ParticleLifetime, Player, Pools, Position, ProvidesFood, ProvidesHealing, Quips, Ranged, Regeneration, Renderable, SerializationHelper, SerializeMe, Shield, SingleActivation, Skills, SufferDamage, TeachesSpell, TwoHanded, Vendor, Viewshed, WantsToCastSpell, WantsToDropItem, WantsToMelee, WantsToPickUpItem, WantsToUnequipItem, WantsToUseItem, NaturalAttackDefense};
+use crate::gameclock::GameClock;
+use crate::spawner::PackIds;
+use crate::statistics_system::Statistics;
 
 macro_rules! serialize_individually {
     ($ecs:expr, $ser:expr, $data:expr, $( $type:ty),*) => {
@@ -41,9 +44,12 @@ macro_rules! deserialize_individually {
 pub fn save_game(ecs: &mut World) {
     // create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let clock = *ecs.fetch::<GameClock>();
+    let statistics = *ecs.fetch::<Statistics>();
+    let packs = *ecs.fetch::<PackIds>();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper { map: mapcopy, clock, statistics, packs })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
     // do the serializing
@@ -98,7 +104,32 @@ pub fn save_game(ecs: &mut World) {
             Attributes,
             Skills,
             Pools,
-            NaturalAttackDefense
+            NaturalAttackDefense,
+            Energy,
+            MyTurn,
+            KnownSpell,
+            WantsToCastSpell,
+            TeachesSpell,
+            EquipmentChanged,
+            Overloaded,
+            Shield,
+            TwoHanded,
+            Regeneration,
+            Behaviour,
+            Home,
+            Memory,
+            Asleep,
+            Faction,
+            Reputation,
+            RangedWeapon,
+            WantsToShoot,
+            PackMember,
+            Dialogue,
+            Quests,
+            Schedule,
+            Locked,
+            Key,
+            OpensDoors
         );
     }
     ecs.delete_entity(savehelper).expect("Couldn't clean up helper")
@@ -174,7 +205,32 @@ pub fn load_game(ecs: &mut World) {
             Attributes,
             Skills,
             Pools,
-            NaturalAttackDefense
+            NaturalAttackDefense,
+            Energy,
+            MyTurn,
+            KnownSpell,
+            WantsToCastSpell,
+            TeachesSpell,
+            EquipmentChanged,
+            Overloaded,
+            Shield,
+            TwoHanded,
+            Regeneration,
+            Behaviour,
+            Home,
+            Memory,
+            Asleep,
+            Faction,
+            Reputation,
+            RangedWeapon,
+            WantsToShoot,
+            PackMember,
+            Dialogue,
+            Quests,
+            Schedule,
+            Locked,
+            Key,
+            OpensDoors
         );
     }
     let mut deleteme: Option<Entity> = None;
@@ -187,6 +243,9 @@ pub fn load_game(ecs: &mut World) {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
             worldmap.tile_content = vec![vec![Vec::new(); worldmap.height as usize]; worldmap.width as usize];
+            *ecs.write_resource::<GameClock>() = h.clock;
+            *ecs.write_resource::<Statistics>() = h.statistics;
+            *ecs.write_resource::<PackIds>() = h.packs;
             deleteme = Some(e);
         }
         for (e,_p,pos) in (&entities, &player, &position).join() {
@@ -204,3 +263,108 @@ pub fn delete_save() {
         fs::remove_file("./savegame.json").expect("Unable to delete file");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bracket_lib::random::RandomNumberGenerator;
+
+    use crate::map::Map;
+    use crate::map::tiletype::TileType;
+    use crate::raws::load_raws;
+    use crate::register_components;
+    use crate::spawner::spawn_entity;
+
+    use super::*;
+
+    fn world() -> World {
+        let mut ecs = World::new();
+        register_components(&mut ecs);
+        ecs.insert(SimpleMarkerAllocator::<SerializeMe>::new());
+        ecs.insert(RandomNumberGenerator::seeded(1));
+        ecs.insert(PackIds::default());
+        let mut map = Map::new(1, 16, 16, "Test Map");
+        for column in map.tiles.iter_mut() {
+            column.fill(TileType::Floor);
+        }
+        ecs.insert(map);
+        ecs
+    }
+
+    // Wolves always come with followers, so this is a whole pack, led by the wolf it returns
+    fn spawn_pack(ecs: &mut World, at: (i32, i32)) -> Entity {
+        load_raws();
+        spawn_entity(ecs, &(&at, &"Wolf".to_string()));
+        let positions = ecs.read_storage::<Position>();
+        (&ecs.entities(), &positions)
+            .join()
+            .find(|(_, pos)| (pos.x, pos.y) == at)
+            .map(|(entity, _)| entity)
+            .unwrap()
+    }
+
+    // Saves and reloads just the components a pack is made of
+    fn round_trip(ecs: &mut World) -> World {
+        let mut serializer = serde_json::Serializer::new(Vec::new());
+        {
+            let data = (ecs.entities(), ecs.read_storage::<SimpleMarker<SerializeMe>>());
+            serialize_individually!(ecs, serializer, data, Name, PackMember);
+        }
+        let saved = String::from_utf8(serializer.into_inner()).unwrap();
+
+        let loaded = world();
+        let mut de = serde_json::Deserializer::from_str(&saved);
+        {
+            let mut d = (
+                &mut loaded.entities(),
+                &mut loaded.write_storage::<SimpleMarker<SerializeMe>>(),
+                &mut loaded.write_resource::<SimpleMarkerAllocator<SerializeMe>>(),
+            );
+            deserialize_individually!(loaded, de, d, Name, PackMember);
+        }
+        loaded
+    }
+
+    fn packs(ecs: &World) -> Vec<(String, u32, bool)> {
+        let mut packs: Vec<(String, u32, bool)> = (&ecs.read_storage::<Name>(), &ecs.read_storage::<PackMember>())
+            .join()
+            .map(|(name, member)| (name.name.clone(), member.pack, member.leader))
+            .collect();
+        packs.sort();
+        packs
+    }
+
+    #[test]
+    fn packs_survive_a_save_and_load() {
+        let mut ecs = world();
+        spawn_pack(&mut ecs, (8, 8));
+        let before = packs(&ecs);
+        assert!(before.len() > 1);
+        assert_eq!(before.iter().filter(|(_, _, leader)| *leader).count(), 1);
+        let loaded = round_trip(&mut ecs);
+        assert_eq!(packs(&loaded), before);
+    }
+
+    #[test]
+    fn a_pack_whose_leader_died_can_still_be_saved() {
+        let mut ecs = world();
+        let leader = spawn_pack(&mut ecs, (8, 8));
+        ecs.delete_entity(leader).unwrap();
+        ecs.maintain();
+        let before = packs(&ecs);
+        assert!(before.iter().all(|(_, _, leader)| !*leader));
+        let loaded = round_trip(&mut ecs);
+        assert_eq!(packs(&loaded), before);
+    }
+
+    #[test]
+    fn a_new_pack_never_takes_a_dead_leaders_id() {
+        let mut ecs = world();
+        let leader = spawn_pack(&mut ecs, (4, 4));
+        let first = ecs.read_storage::<PackMember>().get(leader).unwrap().pack;
+        ecs.delete_entity(leader).unwrap();
+        ecs.maintain();
+        let second_leader = spawn_pack(&mut ecs, (12, 12));
+        let second = ecs.read_storage::<PackMember>().get(second_leader).unwrap().pack;
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,98 @@
+use bracket_lib::prelude::{DistanceAlg, Point};
+use specs::prelude::*;
+
+use crate::components::{Asleep, Memory, Name, Position};
+use crate::events::{GameEvent, GameEvents};
+use crate::gamelog::{GameLog, LogCategory};
+use crate::map::Map;
+use crate::monster_ai_system::SEARCH_TURNS;
+
+pub const FIGHT_NOISE: i32 = 8;
+pub const DOOR_NOISE: i32 = 5;
+pub const LOCKPICK_NOISE: i32 = 3;
+pub const DEATH_NOISE: i32 = 6;
+pub const TRAP_NOISE: i32 = 7;
+
+pub struct SoundEvent {
+    pub source: Entity,
+    pub position: Point,
+    pub radius: i32,
+}
+
+/// Noises made since the last time the hearing system ran. Sound isn't blocked by walls.
+#[derive(Default)]
+pub struct Sounds {
+    pub events: Vec<SoundEvent>,
+}
+
+impl Sounds {
+    pub fn emit(&mut self, source: Entity, x: i32, y: i32, radius: i32) {
+        self.events.push(SoundEvent { source, position: Point::new(x, y), radius });
+    }
+}
+
+pub struct HearingSystem {}
+
+impl<'a> System<'a> for HearingSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteExpect<'a, Sounds>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Memory>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut sounds, map, mut gamelog, positions, mut memories, mut asleep, names) = data;
+
+        for (entity, pos, memory) in (&entities, &positions, &mut memories).join() {
+            let my_pos = Point::new(pos.x, pos.y);
+            for sound in sounds.events.iter().filter(|s| s.source != entity) {
+                let distance = DistanceAlg::Pythagoras.distance2d(my_pos, sound.position);
+                if distance > sound.radius as f32 {
+                    continue;
+                }
+                // Sleepers only stir for noises made close by
+                if asleep.get(entity).is_some() {
+                    if distance > sound.radius as f32 / 2.0 {
+                        continue;
+                    }
+                    asleep.remove(entity);
+                    if map.visible_tiles[pos.x as usize][pos.y as usize] {
+                        if let Some(name) = names.get(entity) {
+                            gamelog.add(LogCategory::System, format!("The {} wakes up.", name.name));
+                        }
+                    }
+                }
+                memory.last_seen = Some(sound.position);
+                memory.search_turns = SEARCH_TURNS;
+            }
+        }
+        sounds.events.clear();
+    }
+}
+
+/// Turns things that happened this turn into noises, so that monsters come to see what the commotion was
+pub struct EventNoiseSystem {}
+
+impl<'a> System<'a> for EventNoiseSystem {
+    type SystemData = (ReadExpect<'a, GameEvents>, WriteExpect<'a, Sounds>);
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, mut sounds) = data;
+
+        for event in events.iter() {
+            let (source, radius) = match event {
+                GameEvent::EntityDied { victim, .. } => (*victim, DEATH_NOISE),
+                GameEvent::TrapTriggered { trap, .. } => (*trap, TRAP_NOISE),
+                _ => continue,
+            };
+            if let Some(position) = event.position() {
+                sounds.emit(source, position.x, position.y, radius);
+            }
+        }
+    }
+}
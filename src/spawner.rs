@@ -1,23 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
 use bracket_lib::color::{BLACK, RGB, YELLOW};
 use bracket_lib::prelude::to_cp437;
 use bracket_lib::random::RandomNumberGenerator;
+use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
 
-use crate::components::{Attribute, Attributes, HungerClock, HungerState, Name, Player, Pool, Pools, Position, Renderable, SerializeMe, Skill, Skills, Viewshed};
+use crate::components::{Attribute, Attributes, BlocksTile, Energy, EquipmentChanged, Faction, HungerClock, HungerState, Name, PackMember, Player, Pool, Pools, Position, Quests, Regeneration, Renderable, Reputation, SerializeMe, Skill, Skills, Viewshed};
 use crate::DEBUGGING;
+use crate::energy_system::ACTION_THRESHOLD;
 use crate::gamesystem::{attr_bonus, mana_at_level, player_hp_at_level};
 use crate::map::Map;
-use crate::map::tiletype::TileType;
+use crate::map::tiletype::{tile_walkable, TileType};
 use crate::random_tables::RandomTable;
-use crate::raws::rawmaster::{get_spawn_table_for_depth, spawn_named_entity, SpawnType};
+use crate::raws::rawmaster::{get_spawn_table_for_depth, mob_pack_size, spawn_named_entity, spawn_named_mob, SpawnType};
 use crate::raws::RAWS;
 use crate::rect::Rect;
 
 const MAX_MONSTERS: i32 = 4;
+const PACK_SPREAD: i32 = 3;
+const STARTING_GOLD: i32 = 20;
 
 pub type SpawnList = Vec<((i32, i32), String)>;
 
@@ -28,6 +32,8 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
     skills.skills.insert(Skill::Melee, 1);
     skills.skills.insert(Skill::Defense, 1);
     skills.skills.insert(Skill::Magic, 1);
+    skills.skills.insert(Skill::Stealth, 1);
+    skills.skills.insert(Skill::Lockpicking, 1);
     let player = ecs.create_entity()
         .with(Position {
             x: player_x,
@@ -48,6 +54,10 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .with(Name {
             name: "Player".to_string(),
         })
+        .with(Faction {
+            name: "Player".to_string(),
+        })
+        .with(Reputation::default())
         .with(HungerClock {
             state: HungerState::WellFed,
             hunger_points: 20,
@@ -79,8 +89,14 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
                 max: mana_at_level(11, 1)
             },
             xp: 0,
-            level: 1
+            level: 1,
+            total_weight: 0.0,
+            gold: STARTING_GOLD
         })
+        .with(Quests::default())
+        .with(Energy { current: ACTION_THRESHOLD })
+        .with(EquipmentChanged {})
+        .with(Regeneration { hp_turns: 0, mana_turns: 0 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
     spawn_named_entity(&RAWS.lock().unwrap(), &"RustyLongsword".to_string(), SpawnType::Equipped {by: player}, ecs);
@@ -107,7 +123,8 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&(i32, i32), &String)) {
         SpawnType::AtPosition {x:coords.0, y:coords.1},
         ecs
     );
-    if item_result.is_some() {
+    if let Some(entity) = item_result {
+        spawn_pack(ecs, entity, spawn.1, *coords);
         return;
     }
     // match spawn.1 {
@@ -160,6 +177,71 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&(i32, i32), &String)) {
     // }
 }
 
+/// Hands out pack ids. Entity ids get recycled once their owner dies and are handed out afresh on load,
+/// so packs count up on their own instead, and the count is saved along with the game.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+pub struct PackIds {
+    next: u32
+}
+
+impl PackIds {
+    pub fn allocate(&mut self) -> u32 {
+        self.next += 1;
+        self.next
+    }
+}
+
+// Some mobs turn up with followers, who gather round the first one and take it as their leader
+fn spawn_pack(ecs: &mut World, leader: Entity, key: &String, at: (i32, i32)) {
+    let raws = RAWS.lock().unwrap();
+    let followers = match mob_pack_size(&raws, key) {
+        None => return,
+        Some((n_dice, die_type, bonus)) => {
+            ecs.write_resource::<RandomNumberGenerator>().roll_dice(n_dice, die_type) + bonus
+        }
+    };
+    let mut members = vec![leader];
+    for (x, y) in free_tiles_near(ecs, at, followers) {
+        if let Some(follower) = spawn_named_mob(&raws, key, SpawnType::AtPosition { x, y }, ecs) {
+            members.push(follower);
+        }
+    }
+    let pack = ecs.write_resource::<PackIds>().allocate();
+    let mut pack_members = ecs.write_storage::<PackMember>();
+    for member in members {
+        pack_members
+            .insert(member, PackMember { pack, leader: member == leader })
+            .expect("Unable to insert pack member");
+    }
+}
+
+fn free_tiles_near(ecs: &World, at: (i32, i32), count: i32) -> Vec<(i32, i32)> {
+    let map = ecs.fetch::<Map>();
+    let occupied: HashSet<(i32, i32)> = (&ecs.read_storage::<Position>(), &ecs.read_storage::<BlocksTile>())
+        .join()
+        .map(|(pos, _)| (pos.x, pos.y))
+        .collect();
+    let mut tiles = Vec::new();
+    for radius in 1..=PACK_SPREAD {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (x, y) = (at.0 + dx, at.1 + dy);
+                if tiles.len() as i32 >= count {
+                    return tiles;
+                }
+                // Only look at the outer ring, the inner ones were covered on earlier passes
+                if i32::max(dx.abs(), dy.abs()) != radius || !map.is_tile_in_bounds(x, y) {
+                    continue;
+                }
+                if tile_walkable(map.tiles[x as usize][y as usize]) && !occupied.contains(&(x, y)) {
+                    tiles.push((x, y));
+                }
+            }
+        }
+    }
+    tiles
+}
+
 pub fn spawn_room(map: &Map, rng: &mut RandomNumberGenerator, room: &Rect, map_level: i32, spawn_list: &mut SpawnList) {
     let mut possible_targets: Vec<(i32, i32)> = Vec::new();
     {
@@ -0,0 +1,119 @@
+use bracket_lib::random::RandomNumberGenerator;
+use specs::prelude::*;
+
+use crate::components::{Attributes, Energy, KnownSpell, Name, Pools, Skill, Skills, WantsToCastSpell, WantsToUseItem};
+use crate::energy_system::USE_ITEM_COST;
+use crate::gamelog::{GameLog, LogCategory};
+use crate::gamesystem::{skill_bonus, spell_difficulty};
+use crate::raws::rawmaster::{spawn_named_spell, spell_name};
+use crate::raws::RAWS;
+
+pub struct SpellCastingSystem {}
+
+impl<'a> System<'a> for SpellCastingSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, WantsToCastSpell>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadStorage<'a, KnownSpell>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Pools>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, Skills>,
+        WriteStorage<'a, Energy>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut gamelog,
+            mut rng,
+            mut wants_cast,
+            mut wants_use_item,
+            known_spells,
+            names,
+            mut pools,
+            attributes,
+            skills,
+            mut energy
+        ) = data;
+
+        for (entity, cast) in (&entities, &wants_cast).join() {
+            let spell = known_spells.get(cast.spell).unwrap();
+            let spell_name = &names.get(cast.spell).unwrap().name;
+            let caster_pools = pools.get_mut(entity).unwrap();
+            if caster_pools.mana.current < spell.mana_cost {
+                if entity == *player_entity {
+                    gamelog.add(LogCategory::Combat, format!("You don't have enough mana to cast {}.", spell_name));
+                }
+                continue;
+            }
+            caster_pools.mana.current -= spell.mana_cost;
+
+            let natural_roll = rng.roll_dice(1, 20);
+            let magic_bonus = skills.get(entity).map_or(0, |s| skill_bonus(Skill::Magic, s));
+            let intelligence_bonus = attributes.get(entity).map_or(0, |a| a.intelligence.bonus);
+            let modified_roll = natural_roll + magic_bonus + intelligence_bonus;
+
+            if natural_roll != 1 && (natural_roll == 20 || modified_roll > spell_difficulty(spell.mana_cost)) {
+                wants_use_item
+                    .insert(entity, WantsToUseItem { item: cast.spell, target: cast.target })
+                    .expect("Unable to insert intent");
+            } else {
+                Energy::spend(&mut energy, entity, USE_ITEM_COST);
+                if entity == *player_entity {
+                    gamelog.add(LogCategory::Combat, format!("Your {} spell fizzles.", spell_name));
+                } else if let Some(caster_name) = names.get(entity) {
+                    gamelog.add(LogCategory::Combat, format!("{}'s {} spell fizzles.", caster_name.name, spell_name));
+                }
+            }
+        }
+        wants_cast.clear();
+    }
+}
+
+pub fn learn_spell(ecs: &mut World, owner: Entity, key: &String) {
+    let raws = RAWS.lock().unwrap();
+    let name = match spell_name(&raws, key) {
+        None => return,
+        Some(name) => name,
+    };
+    let already_known = {
+        let known_spells = ecs.read_storage::<KnownSpell>();
+        let names = ecs.read_storage::<Name>();
+        (&known_spells, &names)
+            .join()
+            .any(|(spell, spell_name)| spell.owner == owner && spell_name.name == name)
+    };
+    let is_player = owner == *ecs.fetch::<Entity>();
+
+    if already_known {
+        if is_player {
+            ecs.write_resource::<GameLog>().add(LogCategory::System, format!("You already know {}.", name));
+        }
+        return;
+    }
+    spawn_named_spell(&raws, key, owner, ecs);
+    if is_player {
+        ecs.write_resource::<GameLog>().add(LogCategory::System, format!("You learn the {} spell.", name));
+    }
+}
+
+/// Checked when the player picks a spell, so trying one they can't afford doesn't cost them a turn
+pub fn player_can_cast(ecs: &World, spell: Entity) -> bool {
+    let player = *ecs.fetch::<Entity>();
+    let mana = ecs.read_storage::<Pools>().get(player).map_or(0, |p| p.mana.current);
+    let cost = ecs.read_storage::<KnownSpell>().get(spell).map_or(0, |s| s.mana_cost);
+    if mana >= cost {
+        return true;
+    }
+    if let Some(name) = ecs.read_storage::<Name>().get(spell) {
+        ecs.write_resource::<GameLog>()
+            .add(LogCategory::Combat, format!("You don't have enough mana to cast {}.", name.name));
+    }
+    false
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use specs::prelude::*;
+
+use crate::components::{Artefact, KnownSpell};
+use crate::events::{GameEvent, GameEvents};
+
+/// A running tally of how the player's game has gone
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
+pub struct Statistics {
+    pub kills: i32,
+    pub xp_from_kills: i32,
+    pub damage_dealt: i32,
+    pub damage_taken: i32,
+    pub items_picked_up: i32,
+    pub items_used: i32,
+    pub spells_cast: i32,
+    pub artefacts_found: i32,
+    pub traps_triggered: i32,
+    pub deepest_level: i32,
+    pub doors_opened: i32,
+    pub locks_picked: i32,
+    pub gold_spent: i32,
+    pub gold_earned: i32,
+    pub conversations: i32,
+}
+
+pub struct StatisticsSystem {}
+
+impl<'a> System<'a> for StatisticsSystem {
+    type SystemData = (
+        ReadExpect<'a, GameEvents>,
+        WriteExpect<'a, Statistics>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, KnownSpell>,
+        ReadStorage<'a, Artefact>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (events, mut stats, player, known_spells, artefacts) = data;
+
+        for event in events.iter() {
+            match event {
+                GameEvent::DamageTaken { target, source, amount } => {
+                    if *target == *player {
+                        stats.damage_taken += amount;
+                    } else if *source == Some(*player) {
+                        stats.damage_dealt += amount;
+                    }
+                }
+                GameEvent::EntityDied { killer, xp, .. } if *killer == Some(*player) => {
+                    stats.kills += 1;
+                    stats.xp_from_kills += xp;
+                }
+                GameEvent::ItemPickedUp { item, by } if *by == *player => {
+                    stats.items_picked_up += 1;
+                    if artefacts.get(*item).is_some() {
+                        stats.artefacts_found += 1;
+                    }
+                }
+                // Spells are cast by using them like an item
+                GameEvent::ItemUsed { item, by } if *by == *player => {
+                    if known_spells.get(*item).is_some() {
+                        stats.spells_cast += 1;
+                    } else {
+                        stats.items_used += 1;
+                    }
+                }
+                GameEvent::TrapTriggered { victim, .. } if *victim == *player => stats.traps_triggered += 1,
+                GameEvent::LevelEntered { depth, .. } => stats.deepest_level = i32::max(stats.deepest_level, *depth),
+                GameEvent::DoorOpened { by, .. } if *by == *player => stats.doors_opened += 1,
+                GameEvent::LockPicked { by, success: true, .. } if *by == *player => stats.locks_picked += 1,
+                GameEvent::ItemBought { price } => stats.gold_spent += price,
+                GameEvent::ItemSold { price } => stats.gold_earned += price,
+                GameEvent::ConversationEnded => stats.conversations += 1,
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+
+use bracket_lib::prelude::{a_star_search, BTerm, DijkstraMap, Point};
+use specs::prelude::*;
+
+use crate::components::{BlocksTile, Door, Faction, Hidden, Item, Locked, Name, Pools, Position, Reaction, Reputation};
+use crate::flow_map_system::TerrainView;
+use crate::gamelog::{GameLog, LogCategory};
+use crate::map::{tile_walkable, Map, TileType};
+use crate::monster_ai_system::reaction_to;
+use crate::player::try_move_player;
+use crate::raws::RAWS;
+use crate::RunState;
+
+const EXPLORE_DEPTH: f32 = 2048.0;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum TravelMode {
+    Explore,
+    Stairs,
+    To(Point),
+}
+
+/// A journey the player has set off on, taken one step per turn until something worth stopping for happens
+#[derive(Default)]
+pub struct Travel {
+    mode: Option<TravelMode>,
+    hp: i32,
+    seen_items: HashSet<Entity>,
+}
+
+impl Travel {
+    pub fn stop(&mut self) {
+        self.mode = None;
+    }
+}
+
+pub fn start(ecs: &mut World, mode: TravelMode) -> RunState {
+    if let Some(hostile) = hostile_in_view(ecs) {
+        ecs.fetch_mut::<GameLog>().add(LogCategory::System, format!("Not with the {} watching you.", hostile));
+        return RunState::AwaitingInput;
+    }
+    let player = *ecs.fetch::<Entity>();
+    let hp = ecs.read_storage::<Pools>().get(player).map_or(0, |p| p.hit_points.current);
+    let seen_items = visible_items(ecs).into_iter().map(|(e, _)| e).collect();
+    *ecs.write_resource::<Travel>() = Travel { mode: Some(mode), hp, seen_items };
+    RunState::AwaitingInput
+}
+
+/// Takes the next step of any journey under way. Returns None when there isn't one, so the player is
+/// asked for input as usual; pressing any key calls the journey off.
+pub fn continue_travel(ecs: &mut World, ctx: &BTerm) -> Option<RunState> {
+    let mode = ecs.fetch::<Travel>().mode?;
+    if ctx.key.is_some() {
+        ecs.write_resource::<Travel>().stop();
+        return Some(RunState::AwaitingInput);
+    }
+    if let Some(reason) = reason_to_stop(ecs) {
+        ecs.write_resource::<Travel>().stop();
+        ecs.fetch_mut::<GameLog>().add(LogCategory::System, reason);
+        return Some(RunState::AwaitingInput);
+    }
+    let step = match next_step(ecs, mode) {
+        Ok(step) => step,
+        Err(message) => {
+            ecs.write_resource::<Travel>().stop();
+            ecs.fetch_mut::<GameLog>().add(LogCategory::System, message);
+            return Some(RunState::AwaitingInput);
+        }
+    };
+    let ppos = *ecs.fetch::<Point>();
+    let outcome = try_move_player(step.x - ppos.x, step.y - ppos.y, ecs);
+    if outcome != RunState::PlayerTurn {
+        ecs.write_resource::<Travel>().stop();
+    }
+    Some(outcome)
+}
+
+fn reason_to_stop(ecs: &World) -> Option<String> {
+    if let Some(hostile) = hostile_in_view(ecs) {
+        return Some(format!("You see the {}.", hostile));
+    }
+    let player = *ecs.fetch::<Entity>();
+    let hp = ecs.read_storage::<Pools>().get(player).map_or(0, |p| p.hit_points.current);
+    let mut travel = ecs.write_resource::<Travel>();
+    let hurt = hp < travel.hp;
+    travel.hp = hp;
+    if hurt {
+        return Some("You are hurt!".to_string());
+    }
+    let mut spotted = None;
+    for (item, name) in visible_items(ecs) {
+        if travel.seen_items.insert(item) && spotted.is_none() {
+            spotted = Some(format!("You spot a {}.", name));
+        }
+    }
+    spotted
+}
+
+fn hostile_in_view(ecs: &World) -> Option<String> {
+    let player = *ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let raws = RAWS.lock().unwrap();
+    let reputation = ecs.read_storage::<Reputation>();
+    let player_reputation = reputation.get(player);
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let factions = ecs.read_storage::<Faction>();
+    let names = ecs.read_storage::<Name>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    (&entities, &positions, &factions, &names, !&hidden)
+        .join()
+        .filter(|(e, pos, _, _, _)| *e != player && map.visible_tiles[pos.x as usize][pos.y as usize])
+        .find(|(_, _, faction, _, _)| {
+            reaction_to(&raws, &faction.name, "Player", player_reputation) == Reaction::Attack
+        })
+        .map(|(_, _, _, name, _)| name.name.clone())
+}
+
+fn visible_items(ecs: &World) -> Vec<(Entity, String)> {
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let items = ecs.read_storage::<Item>();
+    let names = ecs.read_storage::<Name>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    (&entities, &positions, &items, &names, !&hidden)
+        .join()
+        .filter(|(_, pos, _, _, _)| map.visible_tiles[pos.x as usize][pos.y as usize])
+        .map(|(e, _, _, name, _)| (e, name.name.clone()))
+        .collect()
+}
+
+// Travel only goes through ground the player has seen. Closed doors are fine, as walking into them opens
+// them, but locked ones and anything else in the way are not.
+fn impassable(ecs: &World) -> HashSet<usize> {
+    let player = *ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let positions = ecs.read_storage::<Position>();
+    let blockers = ecs.read_storage::<BlocksTile>();
+    let doors = ecs.read_storage::<Door>();
+    let locked = ecs.read_storage::<Locked>();
+
+    let mut impassable: HashSet<usize> = (&entities, &positions, &blockers)
+        .join()
+        .filter(|(e, _, _)| *e != player && (doors.get(*e).is_none() || locked.get(*e).is_some()))
+        .map(|(_, pos, _)| map.xy_idx(pos.x, pos.y))
+        .collect();
+    for x in 0..map.width {
+        for y in 0..map.height {
+            if !map.revealed_tiles[x as usize][y as usize] {
+                impassable.insert(map.xy_idx(x, y));
+            }
+        }
+    }
+    impassable
+}
+
+fn next_step(ecs: &World, mode: TravelMode) -> Result<Point, String> {
+    let ppos = *ecs.fetch::<Point>();
+    let impassable = impassable(ecs);
+    let map = ecs.fetch::<Map>();
+    let view = TerrainView::new(&map, &impassable);
+    let start = map.xy_idx(ppos.x, ppos.y);
+
+    let goal = match mode {
+        TravelMode::Explore => None,
+        TravelMode::To(target) => Some(target),
+        TravelMode::Stairs => {
+            let stairs = (0..map.width)
+                .flat_map(|x| (0..map.height).map(move |y| Point::new(x, y)))
+                .find(|p| {
+                    map.revealed_tiles[p.x as usize][p.y as usize]
+                        && map.tiles[p.x as usize][p.y as usize] == TileType::DownStairs
+                });
+            match stairs {
+                None => return Err("You haven't found the way down yet.".to_string()),
+                Some(stairs) => Some(stairs),
+            }
+        }
+    };
+
+    match goal {
+        Some(goal) => {
+            if goal == ppos {
+                return Err(match mode {
+                    TravelMode::Stairs => "You reach the stairs down.".to_string(),
+                    _ => "You have arrived.".to_string(),
+                });
+            }
+            let path = a_star_search(start, map.xy_idx(goal.x, goal.y), &view);
+            if !path.success || path.steps.len() < 2 {
+                return Err("You can't find a way there.".to_string());
+            }
+            Ok(Point::new(path.steps[1] as i32 % map.width, path.steps[1] as i32 / map.width))
+        }
+        None => {
+            // The frontier: seen, passable tiles next to ones that haven't been seen yet
+            let frontier: Vec<usize> = (0..map.width)
+                .flat_map(|x| (0..map.height).map(move |y| (x, y)))
+                .filter(|(x, y)| {
+                    let idx = map.xy_idx(*x, *y);
+                    idx != start
+                        && !impassable.contains(&idx)
+                        && tile_walkable(map.tiles[*x as usize][*y as usize])
+                        && (-1..=1).any(|dx| {
+                            (-1..=1).any(|dy| {
+                                map.is_tile_in_bounds(x + dx, y + dy)
+                                    && !map.revealed_tiles[(x + dx) as usize][(y + dy) as usize]
+                            })
+                        })
+                })
+                .map(|(x, y)| map.xy_idx(x, y))
+                .collect();
+            let nowhere_left = || "There is nowhere left to explore.".to_string();
+            if frontier.is_empty() {
+                return Err(nowhere_left());
+            }
+            let flow = DijkstraMap::new(map.width, map.height, &frontier, &view, EXPLORE_DEPTH);
+            let next = DijkstraMap::find_lowest_exit(&flow, start, &view).ok_or_else(nowhere_left)?;
+            if flow.map[next] >= EXPLORE_DEPTH {
+                return Err(nowhere_left());
+            }
+            Ok(Point::new(next as i32 % map.width, next as i32 / map.width))
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use bracket_lib::color::{BLACK, ORANGE, RGB};
-use bracket_lib::prelude::to_cp437;
+use bracket_lib::prelude::{to_cp437, Point};
 use specs::prelude::*;
 
 use crate::components::{EntityMoved, EntryTrigger, Hidden, InflictsDamage, Name, Position, SingleActivation, SufferDamage};
-use crate::gamelog::GameLog;
+use crate::events::{GameEvent, GameEvents};
+use crate::gamelog::{GameLog, LogCategory};
 use crate::map::Map;
 use crate::particle_system::ParticleBuilder;
 
@@ -22,7 +23,8 @@ impl<'a> System<'a> for TriggerSystem {
         WriteStorage<'a, InflictsDamage>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, ParticleBuilder>,
-        ReadStorage<'a, SingleActivation>
+        ReadStorage<'a, SingleActivation>,
+        WriteExpect<'a, GameEvents>
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -38,7 +40,8 @@ impl<'a> System<'a> for TriggerSystem {
             mut inflicts_damage,
             mut suffer_damage,
             mut particle_builder,
-            single_adctivation
+            single_adctivation,
+            mut events
         ) = data;
 
         for (entity, _em, pos) in (&entities, &mut entities_moved, &positions).join() {
@@ -50,8 +53,13 @@ impl<'a> System<'a> for TriggerSystem {
                             let damage = inflicts_damage.get(*entity_id);
                             if let Some(damage) = damage {
                                 particle_builder.request(pos.x, pos.y, RGB::named(ORANGE), RGB::named(BLACK), to_cp437('!'), 200.0);
-                                SufferDamage::new_damage(&mut suffer_damage, entity, damage.damage)
+                                SufferDamage::new_damage(&mut suffer_damage, entity, damage.damage, false, Some(*entity_id))
                             }
+                            events.publish(GameEvent::TrapTriggered {
+                                trap: *entity_id,
+                                victim: entity,
+                                position: Point::new(pos.x, pos.y),
+                            });
                             let sa = single_adctivation.get(*entity_id);
                             if let Some(_sa) = sa {
                                 inflicts_damage.remove(*entity_id);
@@ -59,7 +67,7 @@ impl<'a> System<'a> for TriggerSystem {
                             }
                             let name = names.get(*entity_id);
                             if let Some(name) = name {
-                                game_log.entries.push(format!("{} triggers!", &name.name));
+                                game_log.add(LogCategory::Combat, format!("{} triggers!", &name.name));
                             }
                             hidden_things.remove(*entity_id);
                         }
@@ -0,0 +1,128 @@
+use specs::prelude::*;
+
+use crate::components::{
+    Artefact, EquipmentChanged, Faction, Item, MyTurn, Name, Pools, Reputation, Vendor,
+};
+use crate::events::{GameEvent, GameEvents};
+use crate::gameclock::{hour_in_span, GameClock};
+use crate::gamelog::{GameLog, LogBuilder, LogCategory};
+use crate::gamesystem::trade_price;
+use crate::raws::rawmaster::{item_name_and_value, spawn_named_item, SpawnType};
+use crate::raws::RAWS;
+
+/// How many of each item a vendor has on hand after restocking
+pub const STOCK_LEVEL: i32 = 3;
+/// Vendor turns between restocks
+pub const RESTOCK_TURNS: i32 = 200;
+
+pub struct VendorSystem {}
+
+impl<'a> System<'a> for VendorSystem {
+    type SystemData = (
+        WriteStorage<'a, Vendor>,
+        ReadStorage<'a, MyTurn>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut vendors, turns) = data;
+
+        for (vendor, _turn) in (&mut vendors, &turns).join() {
+            vendor.restock_timer -= 1;
+            if vendor.restock_timer > 0 {
+                continue;
+            }
+            vendor.restock_timer = RESTOCK_TURNS;
+            for (_, count) in vendor.stock.iter_mut() {
+                *count = i32::max(*count, STOCK_LEVEL);
+            }
+        }
+    }
+}
+
+pub fn is_open(vendor: &Vendor, clock: &GameClock) -> bool {
+    hour_in_span(clock.hour(), vendor.hours.0, vendor.hours.1)
+}
+
+pub fn closed_message(vendor: &Vendor, name: &str) -> String {
+    format!("{} says \"We're closed. Come back at {}:00.\"", name, vendor.hours.0)
+}
+
+/// The player's standing with the faction a vendor belongs to
+pub fn vendor_standing(ecs: &World, vendor: Entity) -> i32 {
+    let player = ecs.fetch::<Entity>();
+    let factions = ecs.read_storage::<Faction>();
+    let reputations = ecs.read_storage::<Reputation>();
+    match (factions.get(vendor), reputations.get(*player)) {
+        (Some(faction), Some(reputation)) => *reputation.standing.get(&faction.name).unwrap_or(&0),
+        _ => 0,
+    }
+}
+
+/// What an item in the player's pack is worth; artefacts are valued individually
+pub fn item_value(ecs: &World, item: Entity) -> f32 {
+    if let Some(artefact) = ecs.read_storage::<Artefact>().get(item) {
+        return artefact.value as f32;
+    }
+    ecs.read_storage::<Item>().get(item).map_or(0.0, |i| i.value)
+}
+
+pub fn buy_item(ecs: &mut World, vendor: Entity, stock_index: usize) {
+    let player = *ecs.fetch::<Entity>();
+    let key = match ecs.read_storage::<Vendor>().get(vendor).and_then(|v| v.stock.get(stock_index)) {
+        Some((key, count)) if *count > 0 => key.clone(),
+        _ => return,
+    };
+    let (name, value) = match item_name_and_value(&RAWS.lock().unwrap(), &key) {
+        None => return,
+        Some(item) => item,
+    };
+    let price = trade_price(value, vendor_standing(ecs, vendor), true);
+    {
+        let mut pools = ecs.write_storage::<Pools>();
+        let player_pools = pools.get_mut(player).unwrap();
+        if player_pools.gold < price {
+            ecs.fetch_mut::<GameLog>().add(LogCategory::Loot, format!("You can't afford the {}.", name));
+            return;
+        }
+        player_pools.gold -= price;
+    }
+    if let Some(vendor) = ecs.write_storage::<Vendor>().get_mut(vendor) {
+        vendor.stock[stock_index].1 -= 1;
+    }
+    spawn_named_item(&RAWS.lock().unwrap(), &key, SpawnType::Carried { by: player }, ecs);
+    ecs.write_resource::<GameEvents>().publish(GameEvent::ItemBought { price });
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player, EquipmentChanged {})
+        .expect("Unable to insert marker");
+    LogBuilder::new(LogCategory::Loot)
+        .append("You buy the ")
+        .item(&name)
+        .append(" for ")
+        .gold(price)
+        .append(" gold.")
+        .log(&mut ecs.fetch_mut::<GameLog>());
+}
+
+pub fn sell_item(ecs: &mut World, vendor: Entity, item: Entity) {
+    let player = *ecs.fetch::<Entity>();
+    let price = trade_price(item_value(ecs, item), vendor_standing(ecs, vendor), false);
+    let name = ecs
+        .read_storage::<Name>()
+        .get(item)
+        .map_or("item".to_string(), |n| n.name.clone());
+    if let Some(pools) = ecs.write_storage::<Pools>().get_mut(player) {
+        pools.gold += price;
+    }
+    ecs.delete_entity(item).expect("Unable to delete item");
+    ecs.write_resource::<GameEvents>().publish(GameEvent::ItemSold { price });
+    ecs.write_storage::<EquipmentChanged>()
+        .insert(player, EquipmentChanged {})
+        .expect("Unable to insert marker");
+    LogBuilder::new(LogCategory::Loot)
+        .append("You sell the ")
+        .item(&name)
+        .append(" for ")
+        .gold(price)
+        .append(" gold.")
+        .log(&mut ecs.fetch_mut::<GameLog>());
+}
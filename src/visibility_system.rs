@@ -2,7 +2,7 @@ use bracket_lib::prelude::{field_of_view, Point};
 use bracket_lib::random::RandomNumberGenerator;
 use specs::prelude::*;
 use crate::components::{BlocksVisibility, Hidden, Name, Player};
-use crate::gamelog::GameLog;
+use crate::gamelog::{GameLog, LogCategory};
 use crate::map::Map;
 use super::{Viewshed, Position};
 
@@ -51,7 +51,7 @@ impl<'a> System<'a> for VisibilitySystem {
                                 if rng.roll_dice(1,24)==1 {
                                     let name = names.get(*e);
                                     if let Some(name) = name {
-                                        log.entries.push(format!("You spotted a {}", &name.name));
+                                        log.add(LogCategory::System, format!("You spotted a {}", &name.name));
                                     }
                                     hidden_things.remove(*e);
                                 }